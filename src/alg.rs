@@ -25,6 +25,11 @@ impl<'a> Iterator for AsciiKeywords<'a> {
     type Item = &'a str;
 
     fn next(&mut self) -> Option<Self::Item> {
+        // Skip the `@` which starts the name of a SQL Server parameter.
+        if self.index == 0 && self.s.first() == Some(&b'@') {
+            self.index = 1;
+        }
+
         if self.index >= self.s.len() {
             return None;
         }
@@ -96,6 +101,11 @@ impl Keywords for String {
 pub trait Name {
     /// Gets the name of this instance.
     fn name(&self) -> &str;
+
+    /// Gets the namespace this instance belongs to, such as the schema containing a table.
+    fn namespace(&self) -> Option<&str> {
+        None
+    }
 }
 
 impl<T> Name for &T
@@ -105,6 +115,10 @@ where
     fn name(&self) -> &str {
         (*self).name()
     }
+
+    fn namespace(&self) -> Option<&str> {
+        (*self).namespace()
+    }
 }
 
 impl<T> Name for &mut T
@@ -114,19 +128,34 @@ where
     fn name(&self) -> &str {
         (**self).name()
     }
+
+    fn namespace(&self) -> Option<&str> {
+        (**self).namespace()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum Match<V> {
     Exact(V),
     Prefix(V),
+    /// Only the namespace of the value matches the pattern.
+    Namespace(V),
 }
 
 impl<V> Match<V> {
     /// Extracts the inner value from the `Match`.
     pub fn into_inner(self) -> V {
         match self {
-            Match::Exact(v) | Match::Prefix(v) => v,
+            Match::Exact(v) | Match::Prefix(v) | Match::Namespace(v) => v,
+        }
+    }
+
+    /// Ranks the kind of match, with better matches ranked lower.
+    fn rank(&self) -> u8 {
+        match self {
+            Match::Exact(_) => 0,
+            Match::Prefix(_) => 1,
+            Match::Namespace(_) => 2,
         }
     }
 }
@@ -135,22 +164,45 @@ impl<V> Match<V>
 where
     V: Name,
 {
+    /// Matches a value against a pattern.
+    ///
+    /// A pattern which only matches the namespace of a value, such as `audit` for `audit.Users`,
+    /// also finds the value, but ranks below any value whose name matches. A pattern of the form
+    /// `namespace.name` only matches values in a matching namespace.
     fn match_named_value(pat: &str, v: V) -> Option<Self> {
-        let name = v.name();
+        let Some((namespace_pat, name_pat)) = pat.rsplit_once('.') else {
+            return match Self::match_name(pat, v.name()) {
+                Some(exact) => Some(Self::new(exact, v)),
+                None => {
+                    Self::match_name(pat, v.namespace()?)?;
+                    Some(Match::Namespace(v))
+                }
+            };
+        };
+
+        let namespace = v.namespace()?;
+        let namespace_exact = Self::match_name(namespace_pat, namespace)?;
+        let name_exact = Self::match_name(name_pat, v.name())?;
+        Some(Self::new(namespace_exact && name_exact, v))
+    }
+
+    /// Matches a name against a pattern, returning whether the match is exact.
+    fn match_name(pat: &str, name: &str) -> Option<bool> {
         if name == pat {
-            return Some(Match::Exact(v));
+            return Some(true);
         }
 
-        let is_match = name.ascii_keywords().any(|k| {
-            let k = k.to_ascii_lowercase();
-            let pat = pat.to_ascii_lowercase();
-            k.starts_with(&pat)
-        });
+        let pat = pat.to_ascii_lowercase();
+        name.ascii_keywords()
+            .any(|k| k.to_ascii_lowercase().starts_with(&pat))
+            .then_some(false)
+    }
 
-        if is_match {
-            Some(Match::Prefix(v))
+    fn new(exact: bool, v: V) -> Self {
+        if exact {
+            Match::Exact(v)
         } else {
-            None
+            Match::Prefix(v)
         }
     }
 }
@@ -158,7 +210,7 @@ where
 impl<V> AsRef<V> for Match<V> {
     fn as_ref(&self) -> &V {
         match self {
-            Match::Exact(v) | Match::Prefix(v) => v,
+            Match::Exact(v) | Match::Prefix(v) | Match::Namespace(v) => v,
         }
     }
 }
@@ -166,7 +218,7 @@ impl<V> AsRef<V> for Match<V> {
 impl<V> AsMut<V> for Match<V> {
     fn as_mut(&mut self) -> &mut V {
         match self {
-            Match::Exact(v) | Match::Prefix(v) => v,
+            Match::Exact(v) | Match::Prefix(v) | Match::Namespace(v) => v,
         }
     }
 }
@@ -176,11 +228,9 @@ where
     V: PartialOrd,
 {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match (self, other) {
-            (Match::Exact(a), Match::Exact(b)) => a.partial_cmp(b),
-            (Match::Prefix(a), Match::Prefix(b)) => a.partial_cmp(b),
-            (Match::Exact(_), Match::Prefix(_)) => Some(std::cmp::Ordering::Less),
-            (Match::Prefix(_), Match::Exact(_)) => Some(std::cmp::Ordering::Greater),
+        match self.rank().cmp(&other.rank()) {
+            std::cmp::Ordering::Equal => self.as_ref().partial_cmp(other.as_ref()),
+            ordering => Some(ordering),
         }
     }
 }
@@ -261,14 +311,17 @@ where
     }
 }
 
+/// Orders matches by how closely they match, then by score.
 fn compare_matches<T>(a: &Match<T>, b: &Match<T>) -> Ordering
 where
     T: Scored,
 {
-    a.as_ref()
-        .score()
-        .partial_cmp(&b.as_ref().score())
-        .unwrap_or(Ordering::Greater)
+    a.rank().cmp(&b.rank()).then_with(|| {
+        a.as_ref()
+            .score()
+            .partial_cmp(&b.as_ref().score())
+            .unwrap_or(Ordering::Greater)
+    })
 }
 
 /// Finds the closest matching value in a given slice based on a partial name search.
@@ -342,6 +395,12 @@ mod test {
         }
     }
 
+    #[test]
+    fn ascii_keywords_of_parameter() {
+        let keywords = "@customer_id".ascii_keywords().collect::<Vec<_>>();
+        assert_eq!(keywords, ["customer", "id"]);
+    }
+
     #[test]
     fn find_best_match_simple_case() {
         let mut items = [
@@ -366,6 +425,76 @@ mod test {
         assert_eq!("baz", best_match.0);
     }
 
+    #[derive(Debug)]
+    struct Qualified(&'static str, &'static str, Option<Score>);
+
+    impl Name for Qualified {
+        fn name(&self) -> &str {
+            self.1
+        }
+
+        fn namespace(&self) -> Option<&str> {
+            Some(self.0)
+        }
+    }
+
+    impl Scored for Qualified {
+        fn score(&self) -> Option<Score> {
+            self.2
+        }
+
+        fn score_mut(&mut self) -> &mut Option<Score> {
+            &mut self.2
+        }
+    }
+
+    #[test]
+    fn find_best_match_with_namespace() {
+        let mut items = [
+            Qualified("dbo", "Users", Some(Score::new(10.0))),
+            Qualified("audit", "Users", Some(Score::new(1.0))),
+            Qualified("audit", "Events", None),
+        ];
+
+        let best_match = find_best_mut("audit.Users", items.iter_mut()).unwrap();
+        assert_eq!(("audit", "Users"), (best_match.0, best_match.1));
+
+        let best_match = find_best_mut("aud.us", items.iter_mut()).unwrap();
+        assert_eq!(("audit", "Users"), (best_match.0, best_match.1));
+
+        let best_match = find_best_mut("users", items.iter_mut()).unwrap();
+        assert_eq!(("dbo", "Users"), (best_match.0, best_match.1));
+
+        assert!(find_best_mut("sales.users", items.iter_mut()).is_none());
+    }
+
+    #[test]
+    fn find_best_match_by_namespace_keyword() {
+        let mut items = [
+            Qualified("dbo", "Users", Some(Score::new(10.0))),
+            Qualified("audit", "Users", Some(Score::new(1.0))),
+        ];
+
+        let best_match = find_best_mut("audit", items.iter_mut()).unwrap();
+        assert_eq!(("audit", "Users"), (best_match.0, best_match.1));
+
+        let best_match = find_best_mut("aud", items.iter_mut()).unwrap();
+        assert_eq!(("audit", "Users"), (best_match.0, best_match.1));
+
+        // A value whose name matches the pattern is preferred over one whose namespace matches,
+        // regardless of their scores.
+        let mut items = [
+            Qualified("audit", "Events", Some(Score::new(10.0))),
+            Qualified("dbo", "audit", Some(Score::new(1.0))),
+            Qualified("dbo", "audit_log", Some(Score::new(5.0))),
+        ];
+        let best_match = find_best_mut("audit", items.iter_mut()).unwrap();
+        assert_eq!(("dbo", "audit"), (best_match.0, best_match.1));
+
+        let best_match = find_best_mut("aud", items.iter_mut()).unwrap();
+        assert_eq!(("dbo", "audit_log"), (best_match.0, best_match.1));
+    }
+
     #[test]
     fn find_best_match_match_not_found() {
         let mut items = [
@@ -0,0 +1,277 @@
+use std::{
+    fs::{self, File, TryLockError},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process, thread,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::schema::Schema;
+
+/// Version of the cache file format written by this version of sq.
+///
+/// Version 0 is a bare schema, written before cache files had a header. Version 1 wraps the
+/// schema in an object along with the version.
+const VERSION: u64 = 1;
+
+/// Number of times to try locking the cache before giving up.
+const LOCK_ATTEMPTS: u32 = 100;
+
+/// Time to wait between attempts to lock the cache.
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+#[derive(Deserialize)]
+struct CacheFile {
+    schema: Schema,
+}
+
+#[derive(Serialize)]
+struct CacheFileRef<'a> {
+    version: u64,
+    schema: &'a Schema,
+}
+
+/// Upgrades the contents of a cache file to the current format version.
+fn migrate(mut value: Value) -> anyhow::Result<Value> {
+    let version = match value.get("version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| anyhow!("invalid cache version: {}", version))?,
+        None => 0,
+    };
+
+    if version > VERSION {
+        return Err(anyhow!("unsupported cache version: {}", version));
+    }
+
+    if version == 0 {
+        value = json!({ "version": 1, "schema": value });
+    }
+
+    Ok(value)
+}
+
+/// Gets the path of the lock file guarding the cache file at `path`.
+fn lock_path(path: &Path) -> PathBuf {
+    path.with_extension("lock")
+}
+
+/// Locks the cache file at `path`, waiting a bounded time for other processes to release it.
+///
+/// The lock is released when the returned lock file is closed.
+fn lock(path: &Path) -> anyhow::Result<File> {
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(path))?;
+
+    for _ in 0..LOCK_ATTEMPTS {
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(TryLockError::WouldBlock) => thread::sleep(LOCK_RETRY_DELAY),
+            Err(TryLockError::Error(err)) => return Err(err.into()),
+        }
+    }
+
+    Err(anyhow!("timed out waiting for schema cache lock"))
+}
+
+/// Loads a cached schema, upgrading it from an older format if needed.
+///
+/// Returns [`None`] if there is no cache file or it could not be read, in which case the schema
+/// should be fetched from the database instead.
+pub fn load(path: &Path) -> Option<Schema> {
+    let file = File::open(path).ok()?;
+    let result = serde_json::from_reader(BufReader::new(file))
+        .map_err(anyhow::Error::from)
+        .and_then(migrate)
+        .and_then(|value| Ok(serde_json::from_value::<CacheFile>(value)?));
+
+    match result {
+        Ok(cache) => {
+            tracing::info!("Loaded schema from {}", path.to_string_lossy());
+            Some(cache.schema)
+        }
+        Err(err) => {
+            tracing::warn!("Ignoring schema cache {}: {}", path.to_string_lossy(), err);
+            None
+        }
+    }
+}
+
+/// Writes a schema to a file, syncing it to disk before returning.
+fn write(path: &Path, schema: &Schema) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(
+        &mut writer,
+        &CacheFileRef {
+            version: VERSION,
+            schema,
+        },
+    )?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    Ok(())
+}
+
+/// Saves a schema to the cache file at `path`, applying `update` to it first.
+///
+/// Other processes may save the same cache concurrently, so the cache is locked while it is read
+/// back and written. The scores of the schema already in the cache are merged into `schema`, and
+/// only then is `update` applied, so that score updates made by other processes since `schema`
+/// was loaded are built upon rather than overwritten. The schema is written to a temporary file
+/// which then replaces the cache file, so that an interrupted save never leaves a truncated cache
+/// behind.
+pub fn save(
+    path: &Path,
+    schema: &mut Schema,
+    update: impl FnOnce(&mut Schema),
+) -> anyhow::Result<()> {
+    let _lock = lock(path)?;
+
+    if let Some(cached) = load(path) {
+        schema.merge_scores(&cached);
+    }
+    update(schema);
+
+    let temp_path = path.with_extension(format!("{}.tmp", process::id()));
+    let result = write(&temp_path, schema).and_then(|()| Ok(fs::rename(&temp_path, path)?));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result?;
+    tracing::info!("Saved schema to {}", path.to_string_lossy());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{self, Score, Scored},
+        schema::{DataType, builder::ObjectBuilder},
+    };
+
+    /// An empty directory for a test's cache files, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("sq-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn join(&self, name: &str) -> PathBuf {
+            self.0.join(name)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn schema() -> Schema {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("users")
+            .column("id", DataType::Integer, false)
+            .view(&mut schema);
+        schema
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new("save");
+        let path = dir.join("default.schema.json");
+        save(&path, &mut schema(), |_| {}).unwrap();
+
+        let value: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["version"].as_u64(), Some(VERSION));
+
+        let schema = load(&path).unwrap();
+        assert_eq!(schema.objects.len(), 1);
+
+        // Only the cache and lock files remain.
+        let mut files = fs::read_dir(&dir.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, ["default.schema.json", "default.schema.lock"]);
+    }
+
+    #[test]
+    fn test_load_legacy() {
+        let dir = TempDir::new("legacy");
+        let path = dir.join("default.schema.json");
+        fs::write(&path, serde_json::to_string(&schema()).unwrap()).unwrap();
+
+        let schema = load(&path).unwrap();
+        assert_eq!(schema.objects.len(), 1);
+    }
+
+    #[test]
+    fn test_load_invalid() {
+        let dir = TempDir::new("invalid");
+        let path = dir.join("default.schema.json");
+
+        fs::write(&path, r#"{"version": 1, "schema": {"obj"#).unwrap();
+        assert!(load(&path).is_none());
+
+        fs::write(&path, r#"{"version": 99, "schema": {}}"#).unwrap();
+        assert!(load(&path).is_none());
+    }
+
+    #[test]
+    fn test_save_keeps_newer_scores() {
+        let dir = TempDir::new("scores");
+        let path = dir.join("default.schema.json");
+
+        // Another process saves a score after this one loaded the schema.
+        let mut first = schema();
+        let mut second = schema();
+        for obj in second.objects.values_mut() {
+            *obj.score_mut() = Some(Score {
+                value: 2.0,
+                timestamp: 100,
+            });
+        }
+        save(&path, &mut second, |_| {}).unwrap();
+        save(&path, &mut first, |_| {}).unwrap();
+
+        let schema = load(&path).unwrap();
+        let obj = schema.objects.values().next().unwrap();
+        assert_eq!(obj.score().map(|s| s.value), Some(2.0));
+    }
+
+    #[test]
+    fn test_save_keeps_concurrent_updates() {
+        let dir = TempDir::new("concurrent");
+        let path = dir.join("default.schema.json");
+        save(&path, &mut schema(), |_| {}).unwrap();
+
+        // Two processes load the schema and use the same object before either saves it.
+        let bump = |schema: &mut Schema| {
+            for obj in schema.objects.values_mut() {
+                alg::update_score(obj.score_mut());
+            }
+        };
+        let mut first = load(&path).unwrap();
+        let mut second = load(&path).unwrap();
+        save(&path, &mut first, bump).unwrap();
+        save(&path, &mut second, bump).unwrap();
+
+        let schema = load(&path).unwrap();
+        let obj = schema.objects.values().next().unwrap();
+        assert_eq!(obj.score().map(|s| s.value), Some(4.0));
+    }
+}
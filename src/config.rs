@@ -17,6 +17,11 @@ use crate::db::DriverConfig;
 pub struct Profile {
     pub driver: DriverConfig,
 
+    /// Number of seconds after which the cached schema is fetched again from the database. The
+    /// cached schema is also fetched again whenever the database reports that it has changed.
+    #[serde(default)]
+    pub cache_ttl: Option<u64>,
+
     #[serde(skip)]
     name: String,
 }
@@ -0,0 +1,320 @@
+use std::sync::OnceLock;
+
+use odbc_api::{
+    Connection, ConnectionOptions, Cursor, Environment, IntoParameter, ParameterCollectionRef,
+    parameter::InputParameter,
+};
+
+use crate::{
+    db::{
+        ColumnInfo, Database, RowSink,
+        postgres::{self, Catalog, CatalogRow},
+    },
+    schema::Schema,
+    sql::{AnsiDialect, OracleDialect, PostgresDialect, SqlDialect, SqlParam, TSql},
+};
+
+mod catalog;
+mod mssql;
+
+static ODBC_ENV: OnceLock<Environment> = OnceLock::new();
+
+/// Timeout in seconds for the queries used to fetch a schema. Queries run by the user are not
+/// limited, as scanning a large table can legitimately take much longer.
+const ODBC_QUERY_TIMEOUT: usize = 10;
+
+/// The database management system behind an ODBC connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dbms {
+    SqlServer,
+    Postgres,
+    Db2,
+    Oracle,
+    Snowflake,
+    Other,
+}
+
+impl Dbms {
+    /// Identifies a database management system from the name reported by its ODBC driver.
+    fn from_name(name: &str) -> Dbms {
+        let name = name.to_ascii_lowercase();
+        if name.contains("sql server") {
+            Dbms::SqlServer
+        } else if name.contains("postgresql") {
+            Dbms::Postgres
+        } else if name.starts_with("db2") {
+            Dbms::Db2
+        } else if name.contains("oracle") {
+            Dbms::Oracle
+        } else if name.contains("snowflake") {
+            Dbms::Snowflake
+        } else {
+            Dbms::Other
+        }
+    }
+
+    fn dialect(self) -> &'static dyn SqlDialect {
+        match self {
+            Dbms::SqlServer => &TSql,
+            Dbms::Postgres => &PostgresDialect::ODBC,
+            Dbms::Oracle => &OracleDialect,
+            Dbms::Db2 | Dbms::Snowflake | Dbms::Other => &AnsiDialect,
+        }
+    }
+}
+
+/// Collects each row of a result using a mapping function.
+///
+/// `NULL` values are passed to `f` as empty slices.
+struct Collect<F, T> {
+    f: F,
+    rows: Vec<T>,
+}
+
+impl<F, T> RowSink for Collect<F, T>
+where
+    F: FnMut(&[&[u8]]) -> T,
+{
+    fn columns(&mut self, _: &[ColumnInfo]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        let record = row
+            .iter()
+            .map(|value| value.map(str::as_bytes).unwrap_or(&[]))
+            .collect::<Vec<_>>();
+
+        self.rows.push((self.f)(&record));
+        Ok(())
+    }
+}
+
+/// Collects each row of a result as text, keeping `NULL` values distinct from empty strings.
+#[derive(Default)]
+struct CatalogRows(Vec<CatalogRow>);
+
+impl RowSink for CatalogRows {
+    fn columns(&mut self, _: &[ColumnInfo]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        self.0.push(CatalogRow(
+            row.iter().map(|v| v.map(String::from)).collect(),
+        ));
+        Ok(())
+    }
+}
+
+/// Reads every row from a cursor, passing each to `sink` as it is fetched.
+///
+/// Values are fetched one at a time into buffers which grow as needed, rather than in batches
+/// into buffers of a fixed size, so that long values such as those of `nvarchar(max)` columns are
+/// read in full instead of being truncated.
+fn fetch(mut cursor: impl Cursor, sink: &mut dyn RowSink) -> anyhow::Result<()> {
+    let columns = cursor
+        .column_names()?
+        .map(|name| name.map(|name| ColumnInfo { name }))
+        .collect::<Result<Vec<_>, _>>()?;
+    sink.columns(&columns)?;
+
+    let mut buffers = vec![(Vec::new(), false); columns.len()];
+    while let Some(mut row) = cursor.next_row()? {
+        for (column, (buffer, not_null)) in (1..).zip(buffers.iter_mut()) {
+            *not_null = row.get_text(column, buffer)?;
+        }
+
+        let values = buffers
+            .iter()
+            .map(|(buffer, not_null)| not_null.then(|| String::from_utf8_lossy(buffer)))
+            .collect::<Vec<_>>();
+
+        let record = values.iter().map(Option::as_deref).collect::<Vec<_>>();
+        sink.row(&record)?;
+    }
+
+    Ok(())
+}
+
+struct Odbc {
+    connection: Connection<'static>,
+    dbms: Dbms,
+}
+
+impl Odbc {
+    /// Executes a query, passing each row to `sink` as it is fetched.
+    ///
+    /// The query is cancelled if it runs for longer than `timeout` seconds.
+    fn stream(
+        &self,
+        query: &str,
+        params: impl ParameterCollectionRef,
+        timeout: Option<usize>,
+        sink: &mut dyn RowSink,
+    ) -> anyhow::Result<()> {
+        match self.connection.execute(query, params, timeout)? {
+            Some(cursor) => fetch(cursor, sink),
+            None => sink.columns(&[]),
+        }
+    }
+
+    /// Executes a query used to fetch the schema, mapping each row to a value using `f` and
+    /// collecting the results.
+    ///
+    /// `NULL` values are passed to `f` as empty slices.
+    fn exec_query<F, T>(
+        &self,
+        query: &str,
+        params: impl ParameterCollectionRef,
+        f: F,
+    ) -> anyhow::Result<Vec<T>>
+    where
+        F: FnMut(&[&[u8]]) -> T,
+    {
+        let mut collect = Collect {
+            f,
+            rows: Vec::new(),
+        };
+        self.stream(query, params, Some(ODBC_QUERY_TIMEOUT), &mut collect)?;
+        Ok(collect.rows)
+    }
+
+    /// Reads every row from a cursor, such as one returned by a catalog function, mapping each
+    /// row to a value using `f` and collecting the results.
+    ///
+    /// `NULL` values are passed to `f` as empty slices.
+    fn collect<F, T>(&self, cursor: impl Cursor, f: F) -> anyhow::Result<Vec<T>>
+    where
+        F: FnMut(&[&[u8]]) -> T,
+    {
+        let mut collect = Collect {
+            f,
+            rows: Vec::new(),
+        };
+        fetch(cursor, &mut collect)?;
+        Ok(collect.rows)
+    }
+}
+
+/// Runs the PostgreSQL catalog queries through ODBC, so that the schema is read exactly as it is
+/// by the native PostgreSQL driver.
+impl Catalog for Odbc {
+    fn query(&self, sql: &str) -> anyhow::Result<Vec<CatalogRow>> {
+        let mut rows = CatalogRows::default();
+        self.stream(sql, (), Some(ODBC_QUERY_TIMEOUT), &mut rows)?;
+        Ok(rows.0)
+    }
+}
+
+impl Database for Odbc {
+    /// SQL Server and PostgreSQL are introspected through their system catalogs. Other databases,
+    /// including DB2, Oracle and Snowflake, use the ODBC catalog functions, which provide tables,
+    /// views, columns, primary keys and foreign keys but no routines.
+    #[tracing::instrument(skip_all, err)]
+    fn schema(&self) -> anyhow::Result<Schema> {
+        match self.dbms {
+            Dbms::SqlServer => mssql::schema(self),
+            Dbms::Postgres => postgres::schema(self),
+            _ => catalog::schema(self),
+        }
+    }
+
+    fn fingerprint(&self) -> anyhow::Result<Option<String>> {
+        match self.dbms {
+            Dbms::SqlServer => mssql::fingerprint(self),
+            Dbms::Postgres => postgres::fingerprint(self),
+            dbms => catalog::fingerprint(self, dbms),
+        }
+    }
+
+    fn dialect(&self) -> &dyn SqlDialect {
+        self.dbms.dialect()
+    }
+
+    #[tracing::instrument(skip(self, sink), err)]
+    fn execute(
+        &self,
+        sql: &str,
+        params: &[SqlParam],
+        sink: &mut dyn RowSink,
+    ) -> anyhow::Result<()> {
+        let params = params
+            .iter()
+            .map(|param| -> Box<dyn InputParameter> {
+                match param {
+                    SqlParam::String(s) => Box::new(s.clone().into_parameter()),
+                    SqlParam::Int(i) => Box::new(*i),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.stream(sql, params.as_slice(), None, sink)
+    }
+}
+
+/// Initializes a new database connection using ODBC.
+///
+/// The database management system behind the connection is detected from the name reported by
+/// the driver, and used to choose how the schema is fetched and which SQL dialect is used.
+///
+/// # Panics
+///
+/// This function may panic if ODBC environment initialization failed to initialize.
+pub fn connect(connection_string: &str) -> anyhow::Result<Box<dyn Database>> {
+    Ok(Box::new(open(connection_string)?))
+}
+
+fn open(connection_string: &str) -> anyhow::Result<Odbc> {
+    let env =
+        ODBC_ENV.get_or_init(|| Environment::new().expect("Failed to create ODBC environment"));
+
+    let options = ConnectionOptions::default();
+    let connection = env.connect_with_connection_string(connection_string, options)?;
+    let name = connection.database_management_system_name()?;
+    let dbms = Dbms::from_name(&name);
+    tracing::info!("Connected to {} ({:?})", name, dbms);
+
+    Ok(Odbc { connection, dbms })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dbms_from_name() {
+        assert_eq!(Dbms::from_name("Microsoft SQL Server"), Dbms::SqlServer);
+        assert_eq!(Dbms::from_name("PostgreSQL"), Dbms::Postgres);
+        assert_eq!(Dbms::from_name("DB2/LINUXX8664"), Dbms::Db2);
+        assert_eq!(Dbms::from_name("Oracle"), Dbms::Oracle);
+        assert_eq!(Dbms::from_name("Snowflake"), Dbms::Snowflake);
+        assert_eq!(Dbms::from_name("SQLite"), Dbms::Other);
+    }
+
+    /// Runs against the database given by the `SQ_TEST_ODBC_CONNECTION_STRING` environment
+    /// variable, which must be SQL Server or PostgreSQL.
+    #[test]
+    #[ignore]
+    fn test_long_value_integration() {
+        let Ok(connection_string) = std::env::var("SQ_TEST_ODBC_CONNECTION_STRING") else {
+            return;
+        };
+
+        let odbc = open(&connection_string).unwrap();
+        let sql = match odbc.dbms {
+            Dbms::SqlServer => "SELECT REPLICATE(CAST('x' AS nvarchar(max)), 10000), NULL",
+            _ => "SELECT repeat('x', 10000), NULL",
+        };
+
+        // Both the catalog queries used to fetch the schema and queries run by the user read
+        // values longer than any fixed size buffer in full.
+        let rows = odbc.query(sql).unwrap();
+        assert_eq!(rows[0].0[0].as_ref().map(String::len), Some(10000));
+        assert_eq!(rows[0].0[1], None);
+
+        let mut rows = CatalogRows::default();
+        odbc.execute(sql, &[], &mut rows).unwrap();
+        assert_eq!(rows.0[0].0[0].as_ref().map(String::len), Some(10000));
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+
+use odbc_api::{Preallocated, handles::StatementImpl};
+
+use crate::{
+    db::odbc::{Dbms, Odbc},
+    schema::{Column, ColumnId, DataType, ForeignKey, Key, Object, ObjectId, Schema},
+};
+
+// ODBC SQL data type codes reported by `SQLColumns`.
+const SQL_INTEGER: &str = "4";
+const SQL_SMALLINT: &str = "5";
+const SQL_TINYINT: &str = "-6";
+const SQL_BIGINT: &str = "-5";
+
+/// Maps a column reported by `SQLColumns` to a [`DataType`].
+///
+/// The type is mapped using the database's own name for it, falling back to the ODBC SQL data
+/// type code when the name is not recognised. `COLUMN_SIZE` is the length of character and binary
+/// types and the precision of numeric types.
+fn data_type(code: &str, name: &str, size: &str, digits: &str) -> DataType {
+    let size = size.parse().ok();
+    match DataType::from_name(name, size, size, digits.parse().ok()) {
+        DataType::Other(name) => match code {
+            SQL_INTEGER => DataType::Integer,
+            SQL_SMALLINT => DataType::SmallInt,
+            SQL_TINYINT => DataType::TinyInt,
+            SQL_BIGINT => DataType::BigInt,
+            _ => DataType::Other(name),
+        },
+        data_type => data_type,
+    }
+}
+
+/// An object found while fetching the schema, used to resolve foreign key references by name.
+struct ObjectEntry {
+    id: ObjectId,
+    columns: HashMap<String, ColumnId>,
+}
+
+/// Fetches the schema using the ODBC catalog functions (`SQLTables`, `SQLColumns`,
+/// `SQLPrimaryKeys` and `SQLForeignKeys`).
+///
+/// Every ODBC driver implements these against its own system catalog, so this works for any
+/// database, although it requires two round trips per table to fetch keys. Unique keys and
+/// indexes are only reported by `SQLStatistics`, which odbc-api doesn't expose, so tables
+/// fetched this way have neither.
+#[tracing::instrument(skip_all, err)]
+pub(super) fn schema(odbc: &Odbc) -> anyhow::Result<Schema> {
+    tracing::info!("Fetching database schema using ODBC catalog functions");
+    let mut schema = Schema::default();
+    let catalog = odbc.connection.current_catalog()?;
+    let mut statement = odbc.connection.preallocate()?;
+
+    // Result columns: TABLE_CAT, TABLE_SCHEM, TABLE_NAME, TABLE_TYPE, REMARKS
+    let tables = odbc.collect(
+        statement.tables_cursor(&catalog, "%", "%", "TABLE,VIEW")?,
+        |r| {
+            r.iter()
+                .map(|v| String::from_utf8_lossy(v).into_owned())
+                .collect::<Vec<_>>()
+        },
+    )?;
+
+    // Result columns: TABLE_CAT, TABLE_SCHEM, TABLE_NAME, COLUMN_NAME, DATA_TYPE, TYPE_NAME,
+    // COLUMN_SIZE, BUFFER_LENGTH, DECIMAL_DIGITS, NUM_PREC_RADIX, NULLABLE, REMARKS, COLUMN_DEF, ...
+    let columns = odbc.collect(statement.columns_cursor(&catalog, "%", "%", "%")?, |r| {
+        r.iter()
+            .take(13)
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    })?;
+
+    // Group columns by the schema and table they belong to, so they can be attached to objects.
+    let mut object_columns = HashMap::<(&str, &str), Vec<ColumnId>>::new();
+    for row in &columns {
+        let [
+            _,
+            table_schema,
+            table_name,
+            column_name,
+            data_type_code,
+            type_name,
+            column_size,
+            _,
+            decimal_digits,
+            _,
+            nullable,
+            remarks,
+            column_def,
+        ] = row.as_slice()
+        else {
+            continue;
+        };
+
+        tracing::debug!("Found column: {}", column_name);
+        let column_id = schema.columns.insert_with_key(|id| Column {
+            id,
+            score: None,
+            name: column_name.clone(),
+            data_type: data_type(data_type_code, type_name, column_size, decimal_digits),
+            // SQL_NULLABLE is 1, SQL_NO_NULLS is 0 and SQL_NULLABLE_UNKNOWN is 2.
+            nullable: nullable != "0",
+            default: (!column_def.is_empty()).then(|| column_def.clone()),
+            identity: None,
+            computed: None,
+            description: (!remarks.is_empty()).then(|| remarks.clone()),
+        });
+
+        object_columns
+            .entry((table_schema, table_name))
+            .or_default()
+            .push(column_id);
+    }
+
+    let mut entries = HashMap::new();
+    for row in &tables {
+        let [_, table_schema, table_name, table_type, remarks, ..] = row.as_slice() else {
+            continue;
+        };
+
+        let columns = object_columns
+            .remove(&(table_schema.as_str(), table_name.as_str()))
+            .unwrap_or_default();
+        let column_names = columns
+            .iter()
+            .map(|id| (schema.columns[*id].name.clone(), *id))
+            .collect();
+
+        // Databases without schemas report an empty (NULL) schema for every table.
+        let namespace = (!table_schema.is_empty()).then(|| table_schema.clone());
+        let description = (!remarks.is_empty()).then(|| remarks.clone());
+
+        let id = schema
+            .objects
+            .insert_with_key(|id| match table_type.as_str() {
+                "VIEW" => Object::View {
+                    id,
+                    score: None,
+                    namespace,
+                    name: table_name.clone(),
+                    description,
+                    columns,
+                },
+                _ => Object::Table {
+                    id,
+                    score: None,
+                    namespace,
+                    name: table_name.clone(),
+                    description,
+                    columns,
+                    foreign_keys: Vec::new(),
+                    primary_key: None,
+                    unique_keys: Vec::new(),
+                    indexes: Vec::new(),
+                },
+            });
+
+        tracing::debug!("Found {}: {}.{}", table_type, table_schema, table_name);
+        entries.insert(
+            (table_schema.clone(), table_name.clone()),
+            ObjectEntry {
+                id,
+                columns: column_names,
+            },
+        );
+    }
+
+    // Columns which were not claimed by any table belong to objects of other types, such as
+    // system tables.
+    for ids in object_columns.into_values() {
+        for id in ids {
+            schema.columns.remove(id);
+        }
+    }
+
+    for row in &tables {
+        let [_, table_schema, table_name, table_type, ..] = row.as_slice() else {
+            continue;
+        };
+
+        if table_type != "TABLE" {
+            continue;
+        }
+
+        let entry = &entries[&(table_schema.clone(), table_name.clone())];
+        let key = primary_key(
+            odbc,
+            &mut statement,
+            (&catalog, table_schema, table_name),
+            entry,
+        )?;
+        let fks = foreign_keys(
+            odbc,
+            &mut statement,
+            (&catalog, table_schema, table_name),
+            &entries,
+        )?;
+        if let Some(Object::Table {
+            primary_key,
+            foreign_keys,
+            ..
+        }) = schema.objects.get_mut(entry.id)
+        {
+            *primary_key = key;
+            *foreign_keys = fks;
+        }
+    }
+
+    Ok(schema)
+}
+
+/// Fetches the primary key of a table, given as its catalog, schema and name.
+///
+/// A key with any column which can't be resolved is discarded as a whole.
+fn primary_key(
+    odbc: &Odbc,
+    statement: &mut Preallocated<StatementImpl<'_>>,
+    (catalog, table_schema, table_name): (&str, &str, &str),
+    object: &ObjectEntry,
+) -> anyhow::Result<Option<Key>> {
+    // Result columns: TABLE_CAT, TABLE_SCHEM, TABLE_NAME, COLUMN_NAME, KEY_SEQ, PK_NAME
+    let cursor = statement.primary_keys_cursor(
+        Some(catalog).filter(|catalog| !catalog.is_empty()),
+        Some(table_schema).filter(|schema| !schema.is_empty()),
+        table_name,
+    )?;
+    let mut rows = odbc.collect(cursor, |r| {
+        r.iter()
+            .take(6)
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    })?;
+
+    // Drivers order the result by table rather than by the position of the column in the key.
+    rows.sort_by_key(|row| row.get(4).and_then(|seq| seq.parse::<u32>().ok()));
+
+    let mut key = Key {
+        name: None,
+        columns: Vec::new(),
+    };
+    for row in &rows {
+        let [_, _, _, column_name, _, pk_name] = row.as_slice() else {
+            continue;
+        };
+
+        let Some(column) = object.columns.get(column_name) else {
+            tracing::warn!(
+                "Unable to resolve primary key column: {}.{}",
+                table_name,
+                column_name
+            );
+            return Ok(None);
+        };
+
+        key.name = Some(pk_name.clone()).filter(|name| !name.is_empty());
+        key.columns.push(*column);
+    }
+
+    if key.columns.is_empty() {
+        return Ok(None);
+    }
+
+    tracing::debug!("Found primary key: {}.{}", table_schema, table_name);
+    Ok(Some(key))
+}
+
+/// Fetches the foreign keys declared on a table, given as its catalog, schema and name.
+fn foreign_keys(
+    odbc: &Odbc,
+    statement: &mut Preallocated<StatementImpl<'_>>,
+    (catalog, table_schema, table_name): (&str, &str, &str),
+    objects: &HashMap<(String, String), ObjectEntry>,
+) -> anyhow::Result<Vec<ForeignKey>> {
+    // Result columns: PKTABLE_CAT, PKTABLE_SCHEM, PKTABLE_NAME, PKCOLUMN_NAME, FKTABLE_CAT,
+    // FKTABLE_SCHEM, FKTABLE_NAME, FKCOLUMN_NAME, KEY_SEQ, UPDATE_RULE, DELETE_RULE, FK_NAME, ...
+    let cursor = statement.foreign_keys_cursor("", "", "", catalog, table_schema, table_name)?;
+    let rows = odbc.collect(cursor, |r| {
+        r.iter()
+            .take(12)
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    })?;
+
+    let mut foreign_keys = Vec::new();
+    for row in &rows {
+        let [
+            _,
+            pk_schema,
+            pk_table,
+            pk_column,
+            _,
+            fk_schema,
+            fk_table,
+            fk_column,
+            _,
+            _,
+            _,
+            fk_name,
+        ] = row.as_slice()
+        else {
+            continue;
+        };
+
+        let resolve = |schema: &str, table: &str, column: &str| {
+            let object = objects.get(&(schema.to_string(), table.to_string()))?;
+            Some((object.id, *object.columns.get(column)?))
+        };
+
+        let (Some((_, column)), Some((referenced_object, referenced_column))) = (
+            resolve(fk_schema, fk_table, fk_column),
+            resolve(pk_schema, pk_table, pk_column),
+        ) else {
+            tracing::warn!(
+                "Unable to resolve foreign key: {}.{} -> {}.{}",
+                fk_table,
+                fk_column,
+                pk_table,
+                pk_column
+            );
+            continue;
+        };
+
+        tracing::debug!(
+            "Found foreign key: {}.{} -> {}.{}",
+            fk_table,
+            fk_column,
+            pk_table,
+            pk_column
+        );
+        // The name is optional, without it every foreign key is assumed to have a single column.
+        foreign_keys.push(ForeignKey {
+            constraint: Some(fk_name.clone()).filter(|name| !name.is_empty()),
+            column,
+            referenced_object,
+            referenced_column,
+        });
+    }
+
+    Ok(foreign_keys)
+}
+
+/// Gets a fingerprint of the schema of a database introspected through the catalog functions.
+///
+/// The catalog functions have no way of telling when the schema last changed, so the number of
+/// tables and views and the time any of them was last altered are read from the system views of
+/// the databases which are known. Other databases have no fingerprint, so their cached schema is
+/// only refreshed once it expires.
+#[tracing::instrument(skip_all, err)]
+pub(super) fn fingerprint(odbc: &Odbc, dbms: Dbms) -> anyhow::Result<Option<String>> {
+    let query = match dbms {
+        Dbms::Db2 => "SELECT COUNT(*), MAX(ALTER_TIME) FROM SYSCAT.TABLES",
+        Dbms::Oracle => {
+            "SELECT COUNT(*), TO_CHAR(MAX(LAST_DDL_TIME), 'YYYYMMDDHH24MISS') \
+             FROM ALL_OBJECTS WHERE OBJECT_TYPE IN ('TABLE', 'VIEW')"
+        }
+        Dbms::Snowflake => "SELECT COUNT(*), MAX(LAST_DDL) FROM INFORMATION_SCHEMA.TABLES",
+        Dbms::SqlServer | Dbms::Postgres | Dbms::Other => return Ok(None),
+    };
+
+    let rows = odbc.exec_query(query, (), |r| {
+        r.iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+            .join(":")
+    })?;
+    Ok(rows.into_iter().next())
+}
@@ -0,0 +1,1112 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    alg::Name,
+    db::{Routine, odbc::Odbc},
+    schema::{
+        Column, ColumnId, DataType, ForeignKey, Identity, Index, Key, Object, ObjectId, Parameter,
+        ParameterMode, Schema,
+    },
+};
+
+/// Parses a numeric column from a result, where `NULL` and negative values map to [`None`].
+fn number(value: &[u8]) -> Option<u32> {
+    std::str::from_utf8(value).ok()?.parse().ok()
+}
+
+/// Iterates over the characters of an expression which are not inside a string literal or a
+/// bracketed identifier.
+fn unquoted(expr: &str) -> impl Iterator<Item = char> + '_ {
+    let mut quote = None;
+    expr.chars().filter(move |&c| match quote {
+        Some(q) => {
+            if c == q {
+                quote = None;
+            }
+            false
+        }
+        None => {
+            quote = match c {
+                '\'' | '"' => Some(c),
+                '[' => Some(']'),
+                _ => None,
+            };
+            quote.is_none()
+        }
+    })
+}
+
+/// Removes the parentheses SQL Server wraps around default and computed column expressions, as in
+/// `((0))` or `([Price]*[Quantity])`.
+fn unwrap_parens(mut expr: &str) -> &str {
+    while let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        // Only strip the parentheses if they enclose the whole expression, rather than being the
+        // first and last of several groups as in `(a)+(b)`. Parentheses inside string literals
+        // and identifiers, as in `('a)')`, don't count.
+        let mut depth = 0;
+        let balanced = unquoted(inner).all(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth >= 0
+        });
+
+        if !balanced {
+            break;
+        }
+        expr = inner;
+    }
+
+    expr
+}
+
+/// Maps a type from `INFORMATION_SCHEMA.COLUMNS` to a [`DataType`].
+///
+/// A maximum length of -1 is reported for `varchar(max)` and similar types, which is mapped to an
+/// undeclared length.
+fn data_type(
+    name: &str,
+    length: Option<u32>,
+    precision: Option<u32>,
+    scale: Option<u32>,
+) -> DataType {
+    match name {
+        // `timestamp` is a synonym for `rowversion` rather than a date and time type.
+        "timestamp" | "rowversion" => DataType::Binary(Some(8)),
+        "float" if precision.is_some_and(|p| p <= 24) => DataType::Real,
+        _ => DataType::from_name(name, length, precision, scale),
+    }
+}
+
+/// Objects keyed by namespace and name, along with their columns keyed by name.
+type ObjectIndex = HashMap<(String, String), (ObjectId, HashMap<String, ColumnId>)>;
+
+/// Indexes the objects in a schema, and their columns, by name so that references can be
+/// resolved.
+fn index(schema: &Schema) -> ObjectIndex {
+    schema
+        .objects
+        .values()
+        .map(|obj| {
+            let columns = obj
+                .columns()
+                .iter()
+                .filter_map(|id| schema.columns.get(*id).map(|c| (c.name.clone(), *id)))
+                .collect();
+            let namespace = obj.namespace().unwrap_or_default().to_string();
+            ((namespace, obj.name().to_string()), (obj.id(), columns))
+        })
+        .collect()
+}
+
+/// Fetches primary keys and unique constraints from the database and attaches them to the tables
+/// in `schema`.
+#[tracing::instrument(skip_all, err)]
+fn keys(odbc: &Odbc, schema: &mut Schema) -> anyhow::Result<()> {
+    const QUERY: &str = r#"
+        SELECT
+            [TC].[TABLE_SCHEMA],
+            [TC].[TABLE_NAME],
+            [TC].[CONSTRAINT_NAME],
+            [TC].[CONSTRAINT_TYPE],
+            [KCU].[COLUMN_NAME]
+        FROM
+            [INFORMATION_SCHEMA].[TABLE_CONSTRAINTS] AS [TC]
+            JOIN [INFORMATION_SCHEMA].[KEY_COLUMN_USAGE] AS [KCU]
+                ON [KCU].[CONSTRAINT_SCHEMA] = [TC].[CONSTRAINT_SCHEMA]
+                AND [KCU].[CONSTRAINT_NAME] = [TC].[CONSTRAINT_NAME]
+        WHERE
+            [TC].[CONSTRAINT_TYPE] IN ('PRIMARY KEY', 'UNIQUE')
+        ORDER BY
+            [TC].[TABLE_SCHEMA], [TC].[TABLE_NAME], [TC].[CONSTRAINT_NAME],
+            [KCU].[ORDINAL_POSITION]
+    "#;
+
+    let rows = odbc.exec_query(QUERY, (), |r| {
+        r.iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    })?;
+
+    for (id, is_primary, key) in resolve_keys(&rows, &index(schema)) {
+        if let Some(Object::Table {
+            primary_key,
+            unique_keys,
+            ..
+        }) = schema.objects.get_mut(id)
+        {
+            if is_primary {
+                *primary_key = Some(key);
+            } else {
+                unique_keys.push(key);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches indexes from the database and attaches them to the tables in `schema`.
+///
+/// Heaps, which are listed in `sys.indexes` without a name, are skipped.
+#[tracing::instrument(skip_all, err)]
+fn indexes(odbc: &Odbc, schema: &mut Schema) -> anyhow::Result<()> {
+    const QUERY: &str = r#"
+        SELECT
+            OBJECT_SCHEMA_NAME([I].[object_id]),
+            OBJECT_NAME([I].[object_id]),
+            [I].[name],
+            [I].[is_unique],
+            [I].[type_desc],
+            [C].[name],
+            [IC].[is_included_column],
+            [I].[filter_definition]
+        FROM
+            [sys].[indexes] AS [I]
+            JOIN [sys].[index_columns] AS [IC]
+                ON [IC].[object_id] = [I].[object_id]
+                AND [IC].[index_id] = [I].[index_id]
+            JOIN [sys].[columns] AS [C]
+                ON [C].[object_id] = [IC].[object_id]
+                AND [C].[column_id] = [IC].[column_id]
+        WHERE
+            [I].[type] > 0
+            AND OBJECTPROPERTY([I].[object_id], 'IsUserTable') = 1
+        ORDER BY
+            [I].[object_id], [I].[index_id], [IC].[key_ordinal], [IC].[index_column_id]
+    "#;
+
+    let rows = odbc.exec_query(QUERY, (), |r| {
+        r.iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    })?;
+
+    let objects = index(schema);
+    for (id, index) in resolve_indexes(&rows, &objects) {
+        if let Some(Object::Table { indexes, .. }) = schema.objects.get_mut(id) {
+            indexes.push(index);
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves rows of the index query to the tables they belong to.
+///
+/// Rows are grouped by index, with one row for each key or included column of the index. An index
+/// with any column which can't be resolved is discarded as a whole, rather than being shown with
+/// the wrong columns.
+fn resolve_indexes(rows: &[Vec<String>], objects: &ObjectIndex) -> Vec<(ObjectId, Index)> {
+    let mut indexes = Vec::<((&str, &str), ObjectId, Index)>::new();
+    let mut unresolved = HashSet::new();
+    for row in rows {
+        let [
+            namespace,
+            table,
+            name,
+            is_unique,
+            type_desc,
+            column,
+            is_included,
+            filter,
+        ] = row.as_slice()
+        else {
+            continue;
+        };
+
+        let Some((id, column_id)) = objects
+            .get(&(namespace.clone(), table.clone()))
+            .and_then(|(id, columns)| Some((*id, *columns.get(column)?)))
+        else {
+            tracing::warn!(
+                "Unable to resolve index column {}: {}.{}.{}",
+                name,
+                namespace,
+                table,
+                column
+            );
+            unresolved.insert((namespace.as_str(), table.as_str(), name.as_str()));
+            continue;
+        };
+
+        let table = (namespace.as_str(), table.as_str());
+        let index = match indexes.last_mut() {
+            Some((t, _, index)) if *t == table && &index.name == name => index,
+            _ => {
+                indexes.push((
+                    table,
+                    id,
+                    Index {
+                        name: name.clone(),
+                        columns: Vec::new(),
+                        included_columns: Vec::new(),
+                        unique: is_unique == "1",
+                        clustered: type_desc.starts_with("CLUSTERED"),
+                        filter: (!filter.is_empty()).then(|| unwrap_parens(filter).to_string()),
+                    },
+                ));
+                &mut indexes.last_mut().unwrap().2
+            }
+        };
+
+        if is_included == "1" {
+            index.included_columns.push(column_id);
+        } else {
+            index.columns.push(column_id);
+        }
+    }
+
+    indexes
+        .into_iter()
+        .filter(|((namespace, table), _, index)| {
+            !unresolved.contains(&(*namespace, *table, index.name.as_str()))
+        })
+        .map(|((namespace, table), id, index)| {
+            tracing::debug!("Found index: {}.{}.{}", namespace, table, index.name);
+            (id, index)
+        })
+        .collect()
+}
+
+/// Fetches foreign keys from the database and attaches them to the tables in `schema`.
+#[tracing::instrument(skip_all, err)]
+fn foreign_keys(odbc: &Odbc, schema: &mut Schema) -> anyhow::Result<()> {
+    const QUERY: &str = r#"
+        SELECT
+            OBJECT_NAME([FKC].[constraint_object_id]),
+            OBJECT_SCHEMA_NAME([FKC].[parent_object_id]),
+            OBJECT_NAME([FKC].[parent_object_id]),
+            [PC].[name],
+            OBJECT_SCHEMA_NAME([FKC].[referenced_object_id]),
+            OBJECT_NAME([FKC].[referenced_object_id]),
+            [RC].[name]
+        FROM
+            [sys].[foreign_key_columns] AS [FKC]
+            JOIN [sys].[columns] AS [PC]
+                ON [PC].[object_id] = [FKC].[parent_object_id]
+                AND [PC].[column_id] = [FKC].[parent_column_id]
+            JOIN [sys].[columns] AS [RC]
+                ON [RC].[object_id] = [FKC].[referenced_object_id]
+                AND [RC].[column_id] = [FKC].[referenced_column_id]
+        ORDER BY
+            [FKC].[constraint_object_id], [FKC].[constraint_column_id]
+    "#;
+
+    let rows = odbc.exec_query(QUERY, (), |r| {
+        r.iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    })?;
+
+    let objects = index(schema);
+    for (object, fk) in resolve_foreign_keys(&rows, &objects) {
+        if let Some(Object::Table { foreign_keys, .. }) = schema.objects.get_mut(object) {
+            foreign_keys.push(fk);
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves the rows of the foreign key query, one for each column of a constraint, to the
+/// objects and columns of a schema. Returns the foreign keys along with the tables declaring them.
+///
+/// A constraint is discarded entirely if any of its columns can't be resolved, as joining on only
+/// some of the columns of a composite foreign key would match the wrong rows.
+fn resolve_foreign_keys(
+    rows: &[Vec<String>],
+    objects: &ObjectIndex,
+) -> Vec<(ObjectId, ForeignKey)> {
+    let resolve = |namespace: &str, table: &str, column: &str| {
+        let (id, columns) = objects.get(&(namespace.to_string(), table.to_string()))?;
+        Some((*id, *columns.get(column)?))
+    };
+
+    // Constraints are identified by their schema and name, as constraint names are unique within
+    // a schema.
+    let mut resolved = Vec::new();
+    let mut unresolved = HashSet::new();
+    for row in rows {
+        let [
+            constraint,
+            namespace,
+            table,
+            column,
+            referenced_namespace,
+            referenced_table,
+            referenced_column,
+        ] = row.as_slice()
+        else {
+            continue;
+        };
+
+        let (Some((object, column_id)), Some((referenced_object, referenced_column_id))) = (
+            resolve(namespace, table, column),
+            resolve(referenced_namespace, referenced_table, referenced_column),
+        ) else {
+            tracing::warn!(
+                "Unable to resolve foreign key {}: {}.{} -> {}.{}",
+                constraint,
+                table,
+                column,
+                referenced_table,
+                referenced_column
+            );
+            unresolved.insert((namespace, constraint));
+            continue;
+        };
+
+        tracing::debug!(
+            "Found foreign key: {}.{} -> {}.{}",
+            table,
+            column,
+            referenced_table,
+            referenced_column
+        );
+        resolved.push((
+            (namespace, constraint),
+            object,
+            ForeignKey {
+                constraint: Some(constraint.clone()),
+                column: column_id,
+                referenced_object,
+                referenced_column: referenced_column_id,
+            },
+        ));
+    }
+
+    resolved
+        .into_iter()
+        .filter(|(constraint, _, _)| !unresolved.contains(constraint))
+        .map(|(_, object, fk)| (object, fk))
+        .collect()
+}
+
+/// Resolves rows of the key query to the tables they belong to, returning each key along with
+/// whether it is the primary key.
+///
+/// Rows are grouped by constraint, with one row for each column of the constraint. A key with any
+/// column which can't be resolved is discarded as a whole, rather than being attached with only
+/// some of its columns.
+fn resolve_keys(rows: &[Vec<String>], objects: &ObjectIndex) -> Vec<(ObjectId, bool, Key)> {
+    let mut keys = Vec::<((&str, &str, &str), ObjectId, bool, Key)>::new();
+    let mut unresolved = HashSet::new();
+    for row in rows {
+        let [namespace, table, constraint, constraint_type, column] = row.as_slice() else {
+            continue;
+        };
+
+        let name = (namespace.as_str(), table.as_str(), constraint.as_str());
+        let Some((id, column_id)) = objects
+            .get(&(namespace.clone(), table.clone()))
+            .and_then(|(id, columns)| Some((*id, *columns.get(column)?)))
+        else {
+            tracing::warn!(
+                "Unable to resolve key column {}: {}.{}.{}",
+                constraint,
+                namespace,
+                table,
+                column
+            );
+            unresolved.insert(name);
+            continue;
+        };
+
+        match keys.last_mut() {
+            Some((n, _, _, key)) if *n == name => key.columns.push(column_id),
+            _ => keys.push((
+                name,
+                id,
+                constraint_type == "PRIMARY KEY",
+                Key {
+                    name: Some(constraint.clone()),
+                    columns: vec![column_id],
+                },
+            )),
+        }
+    }
+
+    keys.into_iter()
+        .filter(|(name, _, _, _)| !unresolved.contains(name))
+        .map(|((namespace, table, constraint), id, is_primary, key)| {
+            tracing::debug!("Found key: {}.{}.{}", namespace, table, constraint);
+            (id, is_primary, key)
+        })
+        .collect()
+}
+
+/// Fetches stored procedures and functions, along with their parameters and the columns returned
+/// by table-valued functions, and adds them to `schema`.
+#[tracing::instrument(skip_all, err)]
+fn routines(odbc: &Odbc, schema: &mut Schema) -> anyhow::Result<()> {
+    // The return value of a scalar function is listed as a parameter with [IS_RESULT] set, but
+    // its type is also available from [ROUTINES].
+    const QUERY: &str = r#"
+        SELECT
+            [R].[ROUTINE_SCHEMA],
+            [R].[ROUTINE_NAME],
+            [R].[ROUTINE_TYPE],
+            [R].[DATA_TYPE],
+            [R].[CHARACTER_MAXIMUM_LENGTH],
+            [R].[NUMERIC_PRECISION],
+            [R].[NUMERIC_SCALE],
+            [P].[PARAMETER_NAME],
+            [P].[PARAMETER_MODE],
+            [P].[DATA_TYPE],
+            [P].[CHARACTER_MAXIMUM_LENGTH],
+            [P].[NUMERIC_PRECISION],
+            [P].[NUMERIC_SCALE]
+        FROM
+            [INFORMATION_SCHEMA].[ROUTINES] AS [R]
+            LEFT JOIN [INFORMATION_SCHEMA].[PARAMETERS] AS [P]
+                ON [P].[SPECIFIC_SCHEMA] = [R].[SPECIFIC_SCHEMA]
+                AND [P].[SPECIFIC_NAME] = [R].[SPECIFIC_NAME]
+                AND [P].[IS_RESULT] = 'NO'
+        ORDER BY
+            [R].[ROUTINE_SCHEMA], [R].[ROUTINE_NAME], [P].[ORDINAL_POSITION]
+    "#;
+
+    const COLUMNS_QUERY: &str = r#"
+        SELECT
+            [TABLE_SCHEMA],
+            [TABLE_NAME],
+            [COLUMN_NAME],
+            [IS_NULLABLE],
+            [DATA_TYPE],
+            [CHARACTER_MAXIMUM_LENGTH],
+            [NUMERIC_PRECISION],
+            [NUMERIC_SCALE]
+        FROM
+            [INFORMATION_SCHEMA].[ROUTINE_COLUMNS]
+        ORDER BY
+            [TABLE_SCHEMA], [TABLE_NAME], [ORDINAL_POSITION]
+    "#;
+
+    let to_strings = |r: &[&[u8]]| {
+        r.iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    // Rows are grouped by routine, with one row for each parameter. Routines without parameters
+    // have a single row with an empty parameter name.
+    let rows = odbc.exec_query(QUERY, (), to_strings)?;
+    let mut routines = Vec::<Routine>::new();
+    for row in &rows {
+        let [
+            namespace,
+            name,
+            routine_type,
+            return_type,
+            return_length,
+            return_precision,
+            return_scale,
+            parameter_name,
+            parameter_mode,
+            parameter_type,
+            parameter_length,
+            parameter_precision,
+            parameter_scale,
+        ] = row.as_slice()
+        else {
+            continue;
+        };
+
+        if routines.last().is_none_or(|routine| {
+            routine.namespace.as_ref() != Some(namespace) || routine.name != *name
+        }) {
+            tracing::debug!("Found routine: {}.{}", namespace, name);
+            let return_type = match return_type.as_str() {
+                "" | "TABLE" => None,
+                _ => Some(data_type(
+                    return_type,
+                    number(return_length.as_bytes()),
+                    number(return_precision.as_bytes()),
+                    number(return_scale.as_bytes()),
+                )),
+            };
+
+            routines.push(Routine {
+                namespace: Some(namespace.clone()),
+                name: name.clone(),
+                // Descriptions are attached once every object has been fetched.
+                description: None,
+                is_procedure: routine_type == "PROCEDURE",
+                return_type,
+                parameters: Vec::new(),
+                columns: Vec::new(),
+            });
+        }
+
+        if parameter_name.is_empty() {
+            continue;
+        }
+
+        let column = schema.columns.insert_with_key(|id| Column {
+            id,
+            score: None,
+            name: parameter_name.clone(),
+            data_type: data_type(
+                parameter_type,
+                number(parameter_length.as_bytes()),
+                number(parameter_precision.as_bytes()),
+                number(parameter_scale.as_bytes()),
+            ),
+            nullable: true,
+            default: None,
+            identity: None,
+            computed: None,
+            description: None,
+        });
+
+        if let Some(routine) = routines.last_mut() {
+            routine.parameters.push(Parameter {
+                column,
+                mode: ParameterMode::from_name(parameter_mode),
+            });
+        }
+    }
+
+    let rows = odbc.exec_query(COLUMNS_QUERY, (), to_strings)?;
+    let mut routine_columns = HashMap::<(String, String), Vec<ColumnId>>::new();
+    for row in &rows {
+        let [
+            namespace,
+            name,
+            column_name,
+            nullable,
+            column_type,
+            length,
+            precision,
+            scale,
+        ] = row.as_slice()
+        else {
+            continue;
+        };
+
+        let column = schema.columns.insert_with_key(|id| Column {
+            id,
+            score: None,
+            name: column_name.clone(),
+            data_type: data_type(
+                column_type,
+                number(length.as_bytes()),
+                number(precision.as_bytes()),
+                number(scale.as_bytes()),
+            ),
+            nullable: nullable == "YES",
+            default: None,
+            identity: None,
+            computed: None,
+            description: None,
+        });
+
+        routine_columns
+            .entry((namespace.clone(), name.clone()))
+            .or_default()
+            .push(column);
+    }
+
+    for mut routine in routines {
+        let key = (
+            routine.namespace.clone().unwrap_or_default(),
+            routine.name.clone(),
+        );
+        routine.columns = routine_columns.remove(&key).unwrap_or_default();
+        routine.insert(schema);
+    }
+
+    Ok(())
+}
+
+/// Fetches descriptions from `MS_Description` extended properties and attaches them to the objects
+/// and columns in `schema`.
+///
+/// Properties on an object have a `minor_id` of 0, while those on a column have the column's ID.
+#[tracing::instrument(skip_all, err)]
+fn descriptions(odbc: &Odbc, schema: &mut Schema) -> anyhow::Result<()> {
+    const QUERY: &str = r#"
+        SELECT
+            OBJECT_SCHEMA_NAME([EP].[major_id]),
+            OBJECT_NAME([EP].[major_id]),
+            [C].[name],
+            CAST([EP].[value] AS nvarchar(max))
+        FROM
+            [sys].[extended_properties] AS [EP]
+            LEFT JOIN [sys].[columns] AS [C]
+                ON [C].[object_id] = [EP].[major_id]
+                AND [C].[column_id] = [EP].[minor_id]
+        WHERE
+            [EP].[class] = 1
+            AND [EP].[name] = 'MS_Description'
+            AND ([EP].[minor_id] = 0 OR [C].[column_id] IS NOT NULL)
+    "#;
+
+    let rows = odbc.exec_query(QUERY, (), |r| {
+        r.iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect::<Vec<_>>()
+    })?;
+
+    let objects = index(schema);
+    for row in rows {
+        let [namespace, name, column, description] = row.as_slice() else {
+            continue;
+        };
+
+        let Some((id, columns)) = objects.get(&(namespace.clone(), name.clone())) else {
+            continue;
+        };
+
+        let description = Some(description.clone());
+        if column.is_empty() {
+            tracing::debug!("Found description: {}.{}", namespace, name);
+            match schema.objects.get_mut(*id) {
+                Some(
+                    Object::Table { description: d, .. }
+                    | Object::View { description: d, .. }
+                    | Object::Procedure { description: d, .. }
+                    | Object::Function { description: d, .. },
+                ) => *d = description,
+                None => {}
+            }
+        } else if let Some(column_id) = columns.get(column) {
+            tracing::debug!("Found description: {}.{}.{}", namespace, name, column);
+            if let Some(column) = schema.columns.get_mut(*column_id) {
+                column.description = description;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets a fingerprint of a SQL Server database from the number of user objects and the time any
+/// of them was last modified, which together change whenever an object is created, altered or
+/// dropped.
+#[tracing::instrument(skip_all, err)]
+pub(super) fn fingerprint(odbc: &Odbc) -> anyhow::Result<Option<String>> {
+    const QUERY: &str = r#"
+        SELECT CONCAT(COUNT(*), ':', CONVERT(varchar(33), MAX([modify_date]), 126))
+        FROM [sys].[objects]
+        WHERE [is_ms_shipped] = 0
+    "#;
+
+    let rows = odbc.exec_query(QUERY, (), |r| String::from_utf8_lossy(r[0]).into_owned())?;
+    Ok(rows.into_iter().next())
+}
+
+/// Fetches the schema of a SQL Server database.
+#[tracing::instrument(skip_all, err)]
+pub(super) fn schema(odbc: &Odbc) -> anyhow::Result<Schema> {
+    tracing::info!("Fetching SQL Server database schema");
+    let mut schema = Schema::default();
+
+    // Fetch information from [INFORMATION_SCHEMA] views. Tables with the same name may exist in
+    // different schemas, so both are used to identify a table.
+    const QUERY: &str = r#"
+        SELECT
+            [T].[TABLE_SCHEMA],
+            [T].[TABLE_NAME],
+            [T].[TABLE_TYPE],
+            [C].[COLUMN_NAME],
+            [C].[IS_NULLABLE],
+            [C].[DATA_TYPE],
+            [C].[CHARACTER_MAXIMUM_LENGTH],
+            [C].[NUMERIC_PRECISION],
+            [C].[NUMERIC_SCALE],
+            [C].[COLUMN_DEFAULT],
+            TRY_CAST([IC].[seed_value] AS bigint),
+            TRY_CAST([IC].[increment_value] AS bigint),
+            [CC].[definition],
+            IIF([IC].[object_id] IS NULL, 0, 1)
+        FROM
+            [INFORMATION_SCHEMA].[TABLES] AS [T]
+            JOIN [INFORMATION_SCHEMA].[COLUMNS] AS [C]
+                ON [T].[TABLE_SCHEMA] = [C].[TABLE_SCHEMA]
+                AND [T].[TABLE_NAME] = [C].[TABLE_NAME]
+            LEFT JOIN [sys].[identity_columns] AS [IC]
+                ON [IC].[object_id] = OBJECT_ID(QUOTENAME([T].[TABLE_SCHEMA]) + '.' + QUOTENAME([T].[TABLE_NAME]))
+                AND [IC].[name] = [C].[COLUMN_NAME]
+            LEFT JOIN [sys].[computed_columns] AS [CC]
+                ON [CC].[object_id] = OBJECT_ID(QUOTENAME([T].[TABLE_SCHEMA]) + '.' + QUOTENAME([T].[TABLE_NAME]))
+                AND [CC].[name] = [C].[COLUMN_NAME]
+        ORDER BY
+            [T].[TABLE_SCHEMA], [T].[TABLE_NAME], [C].[ORDINAL_POSITION]
+    "#;
+
+    let mut active_table: Option<(String, String)> = None;
+    let mut active_table_type = None;
+    let mut columns = Vec::new();
+    odbc.exec_query(QUERY, (), |r| {
+        let table_schema = std::str::from_utf8(r[0]).unwrap_or_default();
+        let table_name = std::str::from_utf8(r[1]).unwrap_or_default();
+        let table_type = std::str::from_utf8(r[2]).unwrap_or_default();
+
+        if active_table.is_none() {
+            active_table = Some((table_schema.to_string(), table_name.to_string()));
+            active_table_type = Some(table_type.to_string());
+        } else if active_table
+            .as_ref()
+            .is_some_and(|(s, t)| s != table_schema || t != table_name)
+        {
+            // Store the previous table.
+            let (namespace, name) = active_table.take().unwrap();
+            tracing::debug!("Found table: {}.{}", namespace, name);
+            schema
+                .objects
+                .insert_with_key(|id| match active_table_type.as_deref() {
+                    Some("BASE TABLE") => Object::Table {
+                        id,
+                        score: None,
+                        namespace: Some(namespace),
+                        name,
+                        description: None,
+                        columns: std::mem::take(&mut columns),
+                        foreign_keys: Vec::new(),
+                        primary_key: None,
+                        unique_keys: Vec::new(),
+                        indexes: Vec::new(),
+                    },
+
+                    Some("VIEW") => Object::View {
+                        id,
+                        score: None,
+                        namespace: Some(namespace),
+                        name,
+                        description: None,
+                        columns: std::mem::take(&mut columns),
+                    },
+                    _ => panic!("Unknown table type: {:?}", active_table_type),
+                });
+
+            // Start a new table.
+            active_table = Some((table_schema.to_string(), table_name.to_string()));
+            active_table_type = Some(table_type.to_string());
+        }
+
+        let column_name = std::str::from_utf8(r[3]).unwrap_or_default();
+        let nullable = matches!(r[4], b"YES");
+        let data_type = data_type(
+            std::str::from_utf8(r[5]).unwrap_or_default(),
+            number(r[6]),
+            number(r[7]),
+            number(r[8]),
+        );
+
+        // The seed and increment of decimal identity columns may not fit in a `bigint`, in which
+        // case they are `NULL` and left unknown.
+        let expression = |value: &[u8]| {
+            let value = std::str::from_utf8(value).unwrap_or_default();
+            (!value.is_empty()).then(|| unwrap_parens(value).to_string())
+        };
+        let integer = |value: &[u8]| std::str::from_utf8(value).ok()?.parse().ok();
+        let identity = (r[13] == b"1").then(|| Identity {
+            seed: integer(r[10]),
+            increment: integer(r[11]),
+        });
+
+        let column_id = schema.columns.insert_with_key(|id| Column {
+            id,
+            score: None,
+            name: column_name.to_string(),
+            data_type,
+            nullable,
+            default: expression(r[9]),
+            identity,
+            computed: expression(r[12]),
+            description: None,
+        });
+
+        tracing::debug!("Found column: {}", column_name);
+        columns.push(column_id);
+    })?;
+
+    if let (Some((namespace, name)), Some(active_table_type)) = (active_table, active_table_type) {
+        tracing::debug!("Found table: {}.{}", namespace, name);
+        // Store the last table.
+        schema
+            .objects
+            .insert_with_key(|id| match active_table_type.as_str() {
+                "BASE TABLE" => Object::Table {
+                    id,
+                    score: None,
+                    namespace: Some(namespace),
+                    name,
+                    description: None,
+                    columns: std::mem::take(&mut columns),
+                    foreign_keys: Vec::new(),
+                    primary_key: None,
+                    unique_keys: Vec::new(),
+                    indexes: Vec::new(),
+                },
+
+                "VIEW" => Object::View {
+                    id,
+                    score: None,
+                    namespace: Some(namespace),
+                    name,
+                    description: None,
+                    columns: std::mem::take(&mut columns),
+                },
+                _ => panic!("Unknown table type: {:?}", active_table_type),
+            });
+    }
+
+    keys(odbc, &mut schema)?;
+    indexes(odbc, &mut schema)?;
+    foreign_keys(odbc, &mut schema)?;
+    routines(odbc, &mut schema)?;
+    descriptions(odbc, &mut schema)?;
+
+    Ok(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::builder::ObjectBuilder;
+
+    #[test]
+    fn test_unwrap_parens() {
+        assert_eq!(unwrap_parens("((0))"), "0");
+        assert_eq!(unwrap_parens("(getdate())"), "getdate()");
+        assert_eq!(unwrap_parens("([Price]*[Quantity])"), "[Price]*[Quantity]");
+        assert_eq!(unwrap_parens("(1)+(2)"), "(1)+(2)");
+        assert_eq!(unwrap_parens("N'x'"), "N'x'");
+        assert_eq!(unwrap_parens("('a)')"), "'a)'");
+        assert_eq!(unwrap_parens("('(')+(')')"), "('(')+(')')");
+        assert_eq!(unwrap_parens("([a)b])"), "[a)b]");
+    }
+
+    fn row<const N: usize>(values: [&str; N]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_resolve_foreign_keys() {
+        let mut schema = Schema::default();
+        let orders = ObjectBuilder::new("Orders")
+            .namespace("dbo")
+            .columns(&["Region", "Number", "UserId"])
+            .table(&mut schema);
+        let lines = ObjectBuilder::new("Lines")
+            .namespace("dbo")
+            .columns(&["Region", "Number", "ProductId"])
+            .table(&mut schema);
+        let users = ObjectBuilder::new("Users")
+            .namespace("dbo")
+            .columns(&["Id"])
+            .table(&mut schema);
+        let objects = index(&schema);
+
+        let rows = [
+            row([
+                "FK_Orders_Users",
+                "dbo",
+                "Orders",
+                "UserId",
+                "dbo",
+                "Users",
+                "Id",
+            ]),
+            row([
+                "FK_Lines_Orders",
+                "dbo",
+                "Lines",
+                "Region",
+                "dbo",
+                "Orders",
+                "Region",
+            ]),
+            row([
+                "FK_Lines_Orders",
+                "dbo",
+                "Lines",
+                "Number",
+                "dbo",
+                "Orders",
+                "Number",
+            ]),
+            // The referenced table is not part of the schema.
+            row([
+                "FK_Lines_Products",
+                "dbo",
+                "Lines",
+                "ProductId",
+                "dbo",
+                "Products",
+                "Id",
+            ]),
+        ];
+
+        let foreign_keys = resolve_foreign_keys(&rows, &objects);
+        let names = foreign_keys
+            .iter()
+            .map(|(object, fk)| {
+                (
+                    *object,
+                    fk.constraint.as_deref().unwrap(),
+                    schema.columns[fk.column].name.as_str(),
+                    fk.referenced_object,
+                    schema.columns[fk.referenced_column].name.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (orders, "FK_Orders_Users", "UserId", users, "Id"),
+                (lines, "FK_Lines_Orders", "Region", orders, "Region"),
+                (lines, "FK_Lines_Orders", "Number", orders, "Number"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_partial_foreign_key() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("Orders")
+            .namespace("dbo")
+            .columns(&["Region", "Number"])
+            .table(&mut schema);
+        ObjectBuilder::new("Lines")
+            .namespace("dbo")
+            .columns(&["Region", "Number"])
+            .table(&mut schema);
+        let objects = index(&schema);
+
+        // The second column of the composite key is missing from the referenced table, so the
+        // whole constraint is discarded rather than joining on the first column alone.
+        let rows = [
+            row([
+                "FK_Lines_Orders",
+                "dbo",
+                "Lines",
+                "Region",
+                "dbo",
+                "Orders",
+                "Region",
+            ]),
+            row([
+                "FK_Lines_Orders",
+                "dbo",
+                "Lines",
+                "Number",
+                "dbo",
+                "Orders",
+                "Num",
+            ]),
+        ];
+
+        assert!(resolve_foreign_keys(&rows, &objects).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_keys() {
+        let mut schema = Schema::default();
+        let orders = ObjectBuilder::new("Orders")
+            .namespace("dbo")
+            .columns(&["Region", "Number", "Code"])
+            .table(&mut schema);
+        let objects = index(&schema);
+
+        // The unique key on a column missing from the schema is discarded as a whole, rather than
+        // being attached with only its resolved column.
+        let rows = [
+            row(["dbo", "Orders", "PK_Orders", "PRIMARY KEY", "Region"]),
+            row(["dbo", "Orders", "PK_Orders", "PRIMARY KEY", "Number"]),
+            row(["dbo", "Orders", "UQ_Orders_Code", "UNIQUE", "Code"]),
+            row(["dbo", "Orders", "UQ_Orders_Legacy", "UNIQUE", "Code"]),
+            row(["dbo", "Orders", "UQ_Orders_Legacy", "UNIQUE", "Legacy"]),
+        ];
+
+        let keys = resolve_keys(&rows, &objects)
+            .into_iter()
+            .map(|(object, is_primary, key)| {
+                let columns = key
+                    .columns
+                    .iter()
+                    .map(|id| schema.columns[*id].name.as_str())
+                    .collect::<Vec<_>>();
+                (object, is_primary, key.name.unwrap(), columns)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                (
+                    orders,
+                    true,
+                    "PK_Orders".to_string(),
+                    vec!["Region", "Number"]
+                ),
+                (orders, false, "UQ_Orders_Code".to_string(), vec!["Code"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_indexes() {
+        let mut schema = Schema::default();
+        let orders = ObjectBuilder::new("Orders")
+            .namespace("dbo")
+            .columns(&["Region", "Number"])
+            .table(&mut schema);
+        let objects = index(&schema);
+
+        let rows = [
+            row([
+                "dbo",
+                "Orders",
+                "IX_Region",
+                "0",
+                "NONCLUSTERED",
+                "Region",
+                "0",
+                "",
+            ]),
+            row([
+                "dbo",
+                "Orders",
+                "IX_Region",
+                "0",
+                "NONCLUSTERED",
+                "Number",
+                "1",
+                "",
+            ]),
+            row([
+                "dbo",
+                "Orders",
+                "IX_Legacy",
+                "0",
+                "NONCLUSTERED",
+                "Region",
+                "0",
+                "([Number]>(0))",
+            ]),
+            row([
+                "dbo",
+                "Orders",
+                "IX_Legacy",
+                "0",
+                "NONCLUSTERED",
+                "Legacy",
+                "0",
+                "([Number]>(0))",
+            ]),
+        ];
+
+        let indexes = resolve_indexes(&rows, &objects);
+        assert_eq!(indexes.len(), 1);
+        let (id, index) = &indexes[0];
+        assert_eq!(*id, orders);
+        assert_eq!(index.name, "IX_Region");
+        assert_eq!(index.columns.len(), 1);
+        assert_eq!(index.included_columns.len(), 1);
+    }
+}
@@ -0,0 +1,1347 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    error::Error,
+    str::FromStr,
+};
+
+use anyhow::anyhow;
+use bytes::BytesMut;
+use postgres::{
+    Client, NoTls,
+    fallible_iterator::FallibleIterator,
+    types::{Format, FromSql, IsNull, ToSql, Type, to_sql_checked},
+};
+
+use crate::{
+    db::{ColumnInfo, Database, Routine, RowSink},
+    schema::{
+        Column, ColumnId, DataType, ForeignKey, Identity, Index, Key, Object, ObjectId, Parameter,
+        ParameterMode, Schema,
+    },
+    sql::{PostgresDialect, SqlDialect, SqlParam},
+};
+
+struct Postgres {
+    client: RefCell<Client>,
+}
+
+/// Parameters are sent using the text format so that the server parses them according to the type
+/// it infers for each placeholder. This allows a string to be compared against a column of any
+/// type, just as a literal would be.
+impl ToSql for SqlParam {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            SqlParam::String(s) => out.extend_from_slice(s.as_bytes()),
+            SqlParam::Int(i) => out.extend_from_slice(i.to_string().as_bytes()),
+        }
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    fn encode_format(&self, _ty: &Type) -> Format {
+        Format::Text
+    }
+
+    to_sql_checked!();
+}
+
+/// The textual representation of a value in a query result.
+///
+/// Results are always received in the binary format, so only a handful of common types can be
+/// decoded. The [`PostgresDialect`] casts every projected column to `text` so that this is never
+/// a problem for generated queries.
+struct Text(String);
+
+impl<'a> FromSql<'a> for Text {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let text = match *ty {
+            Type::BOOL => bool::from_sql(ty, raw)?.to_string(),
+            Type::INT2 => i16::from_sql(ty, raw)?.to_string(),
+            Type::INT4 => i32::from_sql(ty, raw)?.to_string(),
+            Type::INT8 => i64::from_sql(ty, raw)?.to_string(),
+            Type::OID => u32::from_sql(ty, raw)?.to_string(),
+            Type::FLOAT4 => f32::from_sql(ty, raw)?.to_string(),
+            Type::FLOAT8 => f64::from_sql(ty, raw)?.to_string(),
+            _ => String::from_sql(ty, raw)?,
+        };
+
+        Ok(Text(text))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(
+            *ty,
+            Type::BOOL
+                | Type::INT2
+                | Type::INT4
+                | Type::INT8
+                | Type::OID
+                | Type::FLOAT4
+                | Type::FLOAT8
+        ) || <String as FromSql>::accepts(ty)
+    }
+}
+
+/// Maps a type from `information_schema.columns` to a [`DataType`].
+///
+/// Arrays, enums and other user defined types are reported by their underlying type name.
+fn data_type(
+    name: &str,
+    udt_name: &str,
+    length: Option<u32>,
+    precision: Option<u32>,
+    scale: Option<u32>,
+) -> DataType {
+    match name {
+        "ARRAY" | "USER-DEFINED" => DataType::Other(udt_name.to_string()),
+        _ => DataType::from_name(name, length, precision, scale),
+    }
+}
+
+/// Runs the catalog queries used to fetch the schema of a PostgreSQL database.
+///
+/// Every value returned by these queries is cast to `text`, so that the schema can be read in the
+/// same way through the native driver and through ODBC.
+pub(super) trait Catalog {
+    fn query(&self, sql: &str) -> anyhow::Result<Vec<CatalogRow>>;
+}
+
+/// A row returned by a catalog query.
+pub(super) struct CatalogRow(pub Vec<Option<String>>);
+
+impl CatalogRow {
+    /// Gets a value which may be `NULL`.
+    fn optional(&self, index: usize) -> Option<String> {
+        self.0.get(index).cloned().flatten()
+    }
+
+    /// Gets a value which is never `NULL`.
+    fn text(&self, index: usize) -> anyhow::Result<String> {
+        self.optional(index)
+            .ok_or_else(|| anyhow!("unexpected null in catalog column {}", index))
+    }
+
+    /// Gets a number, where `NULL` and values out of range map to [`None`].
+    fn number<T: FromStr>(&self, index: usize) -> Option<T> {
+        self.0.get(index)?.as_deref()?.parse().ok()
+    }
+
+    /// Gets a boolean, which PostgreSQL writes as `true` or `false`.
+    fn flag(&self, index: usize) -> bool {
+        self.0
+            .get(index)
+            .is_some_and(|v| v.as_deref() == Some("true"))
+    }
+}
+
+impl Catalog for Postgres {
+    fn query(&self, sql: &str) -> anyhow::Result<Vec<CatalogRow>> {
+        let rows = self.client.borrow_mut().query(sql, &[])?;
+        rows.iter()
+            .map(|row| {
+                let values = (0..row.len())
+                    .map(|i| row.try_get(i))
+                    .collect::<Result<_, _>>()?;
+                Ok(CatalogRow(values))
+            })
+            .collect()
+    }
+}
+
+/// Objects keyed by schema and name, along with their columns keyed by name.
+type ObjectIndex = HashMap<(String, String), (ObjectId, HashMap<String, ColumnId>)>;
+
+/// Fetches primary keys and unique constraints from the database and attaches them to the tables
+/// in `schema`.
+#[tracing::instrument(skip_all, err)]
+fn keys(catalog: &dyn Catalog, schema: &mut Schema, objects: &ObjectIndex) -> anyhow::Result<()> {
+    const QUERY: &str = r#"
+            SELECT
+                "N"."nspname"::text,
+                "T"."relname"::text,
+                "K"."conname"::text,
+                "K"."contype"::text,
+                "C"."attname"::text
+            FROM
+                "pg_catalog"."pg_constraint" AS "K"
+                CROSS JOIN LATERAL unnest("K"."conkey") WITH ORDINALITY AS "U" ("column", "position")
+                JOIN "pg_catalog"."pg_class" AS "T" ON "T"."oid" = "K"."conrelid"
+                JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "T"."relnamespace"
+                JOIN "pg_catalog"."pg_attribute" AS "C"
+                    ON "C"."attrelid" = "K"."conrelid" AND "C"."attnum" = "U"."column"
+            WHERE
+                "K"."contype" IN ('p', 'u')
+                AND "N"."nspname" = ANY (current_schemas(false))
+            ORDER BY
+                "K"."oid", "U"."position"
+        "#;
+
+    // Rows are grouped by constraint, with one row for each column of the constraint. A key with
+    // any column which can't be resolved is discarded as a whole.
+    let rows = catalog.query(QUERY)?;
+    let mut keys = Vec::<((String, String), String, bool, Key)>::new();
+    let mut unresolved = HashSet::new();
+    for row in rows {
+        let table = (row.text(0)?, row.text(1)?);
+        let constraint = row.text(2)?;
+        let constraint_type = row.text(3)?;
+        let column = row.text(4)?;
+
+        let Some(column_id) = objects
+            .get(&table)
+            .and_then(|(_, columns)| columns.get(&column))
+        else {
+            tracing::warn!(
+                "Unable to resolve key column {}: {}.{}.{}",
+                constraint,
+                table.0,
+                table.1,
+                column
+            );
+            unresolved.insert((table, constraint));
+            continue;
+        };
+
+        match keys.last_mut() {
+            Some((t, c, _, key)) if *t == table && *c == constraint => key.columns.push(*column_id),
+            _ => keys.push((
+                table,
+                constraint.clone(),
+                constraint_type == "p",
+                Key {
+                    name: Some(constraint),
+                    columns: vec![*column_id],
+                },
+            )),
+        }
+    }
+
+    for (table, constraint, is_primary, key) in keys {
+        if unresolved.contains(&(table.clone(), constraint.clone())) {
+            continue;
+        }
+
+        tracing::debug!("Found key: {}.{}.{}", table.0, table.1, constraint);
+        let Some((id, _)) = objects.get(&table) else {
+            continue;
+        };
+
+        if let Some(Object::Table {
+            primary_key,
+            unique_keys,
+            ..
+        }) = schema.objects.get_mut(*id)
+        {
+            if is_primary {
+                *primary_key = Some(key);
+            } else {
+                unique_keys.push(key);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches indexes from the database and attaches them to the tables in `schema`.
+///
+/// Indexes on expressions are skipped.
+#[tracing::instrument(skip_all, err)]
+fn indexes(
+    catalog: &dyn Catalog,
+    schema: &mut Schema,
+    objects: &ObjectIndex,
+) -> anyhow::Result<()> {
+    // Key columns come first in `indkey`, followed by any columns from an `INCLUDE` clause.
+    const QUERY: &str = r#"
+            SELECT
+                "N"."nspname"::text,
+                "T"."relname"::text,
+                "I"."relname"::text,
+                "X"."indisunique"::text,
+                "X"."indisclustered"::text,
+                "C"."attname"::text,
+                ("K"."position" > "X"."indnkeyatts")::text,
+                pg_get_expr("X"."indpred", "X"."indrelid")::text
+            FROM
+                "pg_catalog"."pg_index" AS "X"
+                CROSS JOIN LATERAL unnest("X"."indkey"::int2[]) WITH ORDINALITY AS "K" ("column", "position")
+                JOIN "pg_catalog"."pg_class" AS "I" ON "I"."oid" = "X"."indexrelid"
+                JOIN "pg_catalog"."pg_class" AS "T" ON "T"."oid" = "X"."indrelid"
+                JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "T"."relnamespace"
+                JOIN "pg_catalog"."pg_attribute" AS "C"
+                    ON "C"."attrelid" = "X"."indrelid" AND "C"."attnum" = "K"."column"
+            WHERE
+                "X"."indexprs" IS NULL
+                AND "N"."nspname" = ANY (current_schemas(false))
+            ORDER BY
+                "X"."indexrelid", "K"."position"
+        "#;
+
+    // Rows are grouped by index, with one row for each column of the index. An index with any
+    // column which can't be resolved is discarded as a whole, rather than being shown with the
+    // wrong columns.
+    let rows = catalog.query(QUERY)?;
+    let mut indexes = Vec::<((String, String), Index)>::new();
+    let mut unresolved = HashSet::new();
+    for row in rows {
+        let table = (row.text(0)?, row.text(1)?);
+        let name = row.text(2)?;
+        let unique = row.flag(3);
+        let clustered = row.flag(4);
+        let column = row.text(5)?;
+        let included = row.flag(6);
+        let filter = row.optional(7);
+
+        let Some(column_id) = objects
+            .get(&table)
+            .and_then(|(_, columns)| columns.get(&column))
+        else {
+            tracing::warn!(
+                "Unable to resolve index column {}: {}.{}.{}",
+                name,
+                table.0,
+                table.1,
+                column
+            );
+            unresolved.insert((table, name));
+            continue;
+        };
+
+        let index = match indexes.last_mut() {
+            Some((t, index)) if *t == table && index.name == name => index,
+            _ => {
+                indexes.push((
+                    table,
+                    Index {
+                        name,
+                        columns: Vec::new(),
+                        included_columns: Vec::new(),
+                        unique,
+                        clustered,
+                        filter,
+                    },
+                ));
+                &mut indexes.last_mut().unwrap().1
+            }
+        };
+
+        if included {
+            index.included_columns.push(*column_id);
+        } else {
+            index.columns.push(*column_id);
+        }
+    }
+
+    for (table, index) in indexes {
+        if unresolved.contains(&(table.clone(), index.name.clone())) {
+            continue;
+        }
+
+        tracing::debug!("Found index: {}.{}.{}", table.0, table.1, index.name);
+        let Some((id, _)) = objects.get(&table) else {
+            continue;
+        };
+
+        if let Some(Object::Table { indexes, .. }) = schema.objects.get_mut(*id) {
+            indexes.push(index);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches procedures and functions, along with their parameters, and adds them to `schema`.
+///
+/// Functions returning `record`, such as those declared with `RETURNS TABLE`, are treated as
+/// table-valued, with their output parameters as the columns of the table. So are functions
+/// returning `SETOF` a table, view or composite type, with its attributes as the columns.
+#[tracing::instrument(skip_all, err)]
+fn routines(catalog: &dyn Catalog, schema: &mut Schema) -> anyhow::Result<()> {
+    // Functions may be overloaded, so routines are identified by their OID. The arguments of a
+    // function are listed in `proallargtypes` when it has output parameters, and otherwise only
+    // in `proargtypes`, in which case `proargmodes` is null and every argument is an input.
+    const QUERY: &str = r#"
+            SELECT
+                "N"."nspname"::text,
+                "F"."proname"::text,
+                "F"."oid"::text,
+                "F"."prokind"::text,
+                format_type("F"."prorettype", NULL)::text,
+                "A"."position"::text,
+                "A"."name"::text,
+                CASE "A"."mode"
+                    WHEN 'o' THEN 'OUT'
+                    WHEN 't' THEN 'OUT'
+                    WHEN 'b' THEN 'INOUT'
+                    ELSE 'IN'
+                END,
+                format_type("A"."type", NULL)::text,
+                obj_description("F"."oid", 'pg_proc')::text
+            FROM
+                "pg_catalog"."pg_proc" AS "F"
+                JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "F"."pronamespace"
+                LEFT JOIN LATERAL unnest(
+                    coalesce("F"."proallargtypes", "F"."proargtypes"::oid[]),
+                    "F"."proargmodes",
+                    "F"."proargnames"
+                ) WITH ORDINALITY AS "A" ("type", "mode", "name", "position") ON true
+            WHERE
+                "N"."nspname" = ANY (current_schemas(false))
+                AND "F"."prokind" IN ('f', 'p')
+            ORDER BY
+                "N"."nspname", "F"."proname", "F"."oid", "A"."position"
+        "#;
+
+    let rows = catalog.query(QUERY)?;
+    let mut routines = Vec::<(String, Routine)>::new();
+    for row in rows {
+        let oid = row.text(2)?;
+        if routines.last().is_none_or(|(o, _)| *o != oid) {
+            let namespace = row.text(0)?;
+            let name = row.text(1)?;
+            let is_procedure = row.text(3)? == "p";
+            tracing::debug!("Found routine: {}.{}", namespace, name);
+
+            // Functions with output parameters return a record of them.
+            let return_type = match row.text(4)?.as_str() {
+                _ if is_procedure => None,
+                "record" | "void" => None,
+                return_type => Some(DataType::from_name(return_type, None, None, None)),
+            };
+
+            routines.push((
+                oid,
+                Routine {
+                    namespace: Some(namespace),
+                    name,
+                    description: row.optional(9),
+                    is_procedure,
+                    return_type,
+                    parameters: Vec::new(),
+                    columns: Vec::new(),
+                },
+            ));
+        }
+
+        let Some(position) = row.number::<u32>(5) else {
+            continue;
+        };
+
+        // Unnamed parameters can only be referred to by position.
+        let name = row
+            .optional(6)
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("${}", position));
+        let mode = ParameterMode::from_name(&row.text(7)?);
+        let data_type = DataType::from_name(&row.text(8)?, None, None, None);
+
+        let column = schema.columns.insert_with_key(|id| Column {
+            id,
+            score: None,
+            name,
+            data_type,
+            nullable: true,
+            default: None,
+            identity: None,
+            computed: None,
+            description: None,
+        });
+
+        let Some((_, routine)) = routines.last_mut() else {
+            continue;
+        };
+        if !routine.is_procedure && routine.return_type.is_none() && mode == ParameterMode::Out {
+            routine.columns.push(column);
+        } else {
+            routine.parameters.push(Parameter { column, mode });
+        }
+    }
+
+    const COLUMNS_QUERY: &str = r#"
+            SELECT
+                "F"."oid"::text,
+                "C"."attname"::text,
+                format_type("C"."atttypid", NULL)::text,
+                "C"."attnotnull"::text
+            FROM
+                "pg_catalog"."pg_proc" AS "F"
+                JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "F"."pronamespace"
+                JOIN "pg_catalog"."pg_type" AS "T" ON "T"."oid" = "F"."prorettype"
+                JOIN "pg_catalog"."pg_attribute" AS "C" ON "C"."attrelid" = "T"."typrelid"
+            WHERE
+                "N"."nspname" = ANY (current_schemas(false))
+                AND "F"."prokind" = 'f'
+                AND "F"."proretset"
+                AND "C"."attnum" > 0
+                AND NOT "C"."attisdropped"
+            ORDER BY
+                "F"."oid", "C"."attnum"
+        "#;
+
+    let mut routine_columns = HashMap::<String, Vec<ColumnId>>::new();
+    for row in catalog.query(COLUMNS_QUERY)? {
+        let name = row.text(1)?;
+        let data_type = DataType::from_name(&row.text(2)?, None, None, None);
+        let nullable = !row.flag(3);
+        let column = schema.columns.insert_with_key(|id| Column {
+            id,
+            score: None,
+            name,
+            data_type,
+            nullable,
+            default: None,
+            identity: None,
+            computed: None,
+            description: None,
+        });
+        routine_columns
+            .entry(row.text(0)?)
+            .or_default()
+            .push(column);
+    }
+
+    for (oid, mut routine) in routines {
+        if let Some(columns) = routine_columns.remove(&oid) {
+            routine.return_type = None;
+            routine.columns = columns;
+        }
+        routine.insert(schema);
+    }
+
+    Ok(())
+}
+
+/// Fetches foreign keys from the database and attaches them to the tables in `schema`.
+#[tracing::instrument(skip_all, err)]
+fn foreign_keys(
+    catalog: &dyn Catalog,
+    schema: &mut Schema,
+    objects: &ObjectIndex,
+) -> anyhow::Result<()> {
+    const QUERY: &str = r#"
+            SELECT
+                "N"."nspname"::text,
+                "T"."relname"::text,
+                "C"."attname"::text,
+                "RN"."nspname"::text,
+                "RT"."relname"::text,
+                "RC"."attname"::text,
+                "FK"."conname"::text
+            FROM
+                "pg_catalog"."pg_constraint" AS "FK"
+                CROSS JOIN LATERAL unnest("FK"."conkey", "FK"."confkey")
+                    WITH ORDINALITY AS "K" ("column", "referenced_column", "position")
+                JOIN "pg_catalog"."pg_class" AS "T" ON "T"."oid" = "FK"."conrelid"
+                JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "T"."relnamespace"
+                JOIN "pg_catalog"."pg_attribute" AS "C"
+                    ON "C"."attrelid" = "FK"."conrelid" AND "C"."attnum" = "K"."column"
+                JOIN "pg_catalog"."pg_class" AS "RT" ON "RT"."oid" = "FK"."confrelid"
+                JOIN "pg_catalog"."pg_namespace" AS "RN" ON "RN"."oid" = "RT"."relnamespace"
+                JOIN "pg_catalog"."pg_attribute" AS "RC"
+                    ON "RC"."attrelid" = "FK"."confrelid" AND "RC"."attnum" = "K"."referenced_column"
+            WHERE
+                "FK"."contype" = 'f'
+                AND "N"."nspname" = ANY (current_schemas(false))
+            ORDER BY
+                "FK"."oid", "K"."position"
+        "#;
+
+    // Rows are grouped by constraint, with one row for each column of the constraint. A foreign
+    // key with any column which can't be resolved is discarded as a whole, as joining on only some
+    // of the columns of a composite foreign key would match the wrong rows.
+    let rows = catalog.query(QUERY)?;
+    let mut foreign_keys = Vec::<((String, String), String, ObjectId, ForeignKey)>::new();
+    let mut unresolved = HashSet::new();
+    for row in rows {
+        let namespace = row.text(0)?;
+        let table = row.text(1)?;
+        let column = row.text(2)?;
+        let referenced_namespace = row.text(3)?;
+        let referenced_table = row.text(4)?;
+        let referenced_column = row.text(5)?;
+        let constraint = row.text(6)?;
+
+        let resolve = |namespace: &str, table: &str, column: &str| {
+            let (id, columns) = objects.get(&(namespace.to_string(), table.to_string()))?;
+            Some((*id, *columns.get(column)?))
+        };
+
+        let (Some((object, column_id)), Some((referenced_object, referenced_column_id))) = (
+            resolve(&namespace, &table, &column),
+            resolve(&referenced_namespace, &referenced_table, &referenced_column),
+        ) else {
+            tracing::warn!(
+                "Unable to resolve foreign key {}: {}.{} -> {}.{}",
+                constraint,
+                table,
+                column,
+                referenced_table,
+                referenced_column
+            );
+            unresolved.insert(((namespace, table), constraint));
+            continue;
+        };
+
+        foreign_keys.push((
+            (namespace, table),
+            constraint.clone(),
+            object,
+            ForeignKey {
+                constraint: Some(constraint),
+                column: column_id,
+                referenced_object,
+                referenced_column: referenced_column_id,
+            },
+        ));
+    }
+
+    for (table, constraint, object, fk) in foreign_keys {
+        if unresolved.contains(&(table.clone(), constraint)) {
+            continue;
+        }
+
+        tracing::debug!(
+            "Found foreign key: {}.{} -> {}",
+            table.1,
+            schema.columns[fk.column].name,
+            schema.columns[fk.referenced_column].name
+        );
+        if let Some(Object::Table { foreign_keys, .. }) = schema.objects.get_mut(object) {
+            foreign_keys.push(fk);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches the schema of a PostgreSQL database from its system catalogs.
+#[tracing::instrument(skip_all, err)]
+pub(super) fn schema(catalog: &dyn Catalog) -> anyhow::Result<Schema> {
+    tracing::info!("Fetching PostgreSQL database schema");
+    let mut schema = Schema::default();
+
+    // Only objects in the schemas on the search path are visible without qualification, so
+    // only those are fetched.
+    const QUERY: &str = r#"
+            SELECT
+                "T"."table_schema"::text,
+                "T"."table_name"::text,
+                "T"."table_type"::text,
+                "C"."column_name"::text,
+                "C"."is_nullable"::text,
+                "C"."data_type"::text,
+                "C"."udt_name"::text,
+                "C"."character_maximum_length"::text,
+                "C"."numeric_precision"::text,
+                "C"."numeric_scale"::text,
+                "C"."column_default"::text,
+                "C"."is_identity"::text,
+                "C"."identity_start"::text,
+                "C"."identity_increment"::text,
+                "C"."generation_expression"::text,
+                obj_description("R"."oid", 'pg_class')::text,
+                col_description("R"."oid", "C"."ordinal_position"::int4)::text,
+                "S"."seqstart"::text,
+                "S"."seqincrement"::text
+            FROM
+                "information_schema"."tables" AS "T"
+                JOIN "information_schema"."columns" AS "C"
+                    ON "C"."table_schema" = "T"."table_schema"
+                    AND "C"."table_name" = "T"."table_name"
+                JOIN "pg_catalog"."pg_class" AS "R"
+                    ON "R"."oid" = format('%I.%I', "T"."table_schema", "T"."table_name")::regclass
+                LEFT JOIN "pg_catalog"."pg_sequence" AS "S"
+                    ON "S"."seqrelid" = pg_get_serial_sequence(
+                        format('%I.%I', "T"."table_schema", "T"."table_name"),
+                        "C"."column_name"
+                    )::regclass
+            WHERE
+                "T"."table_schema" = ANY (current_schemas(false))
+            ORDER BY
+                "T"."table_schema", "T"."table_name", "C"."ordinal_position"
+        "#;
+
+    let rows = catalog.query(QUERY)?;
+
+    // Rows are grouped by object, so an object is complete once a row for the next is seen.
+    let mut objects = Vec::<(String, String, String, Option<String>, Vec<ColumnId>)>::new();
+    for row in rows {
+        let table_schema = row.text(0)?;
+        let table_name = row.text(1)?;
+        let table_type = row.text(2)?;
+        let column_name = row.text(3)?;
+        let is_nullable = row.text(4)?;
+        let data_type = data_type(
+            &row.text(5)?,
+            &row.text(6)?,
+            row.number(7),
+            row.number(8),
+            row.number(9),
+        );
+
+        // Columns declared as `serial` own a sequence rather than being identity columns, but
+        // are generated in the same way.
+        let identity = match row.optional(11).as_deref() {
+            Some("YES") => Some(Identity {
+                seed: row.number(12),
+                increment: row.number(13),
+            }),
+            _ => row.optional(17).map(|_| Identity {
+                seed: row.number(17),
+                increment: row.number(18),
+            }),
+        };
+        let default = row.optional(10);
+        let computed = row.optional(14);
+        let description = row.optional(16);
+
+        if objects
+            .last()
+            .is_none_or(|(schema, name, ..)| *schema != table_schema || *name != table_name)
+        {
+            tracing::debug!("Found table: {}.{}", table_schema, table_name);
+            let description = row.optional(15);
+            objects.push((
+                table_schema,
+                table_name,
+                table_type,
+                description,
+                Vec::new(),
+            ));
+        }
+
+        tracing::debug!("Found column: {}", column_name);
+        let column_id = schema.columns.insert_with_key(|id| Column {
+            id,
+            score: None,
+            name: column_name,
+            data_type,
+            nullable: is_nullable == "YES",
+            default,
+            identity,
+            computed,
+            description,
+        });
+
+        if let Some((.., columns)) = objects.last_mut() {
+            columns.push(column_id);
+        }
+    }
+
+    let mut entries = HashMap::new();
+    for (namespace, name, table_type, description, columns) in objects {
+        let column_names = columns
+            .iter()
+            .map(|id| (schema.columns[*id].name.clone(), *id))
+            .collect::<HashMap<_, _>>();
+
+        let id = schema
+            .objects
+            .insert_with_key(|id| match table_type.as_str() {
+                "VIEW" => Object::View {
+                    id,
+                    score: None,
+                    namespace: Some(namespace.clone()),
+                    name: name.clone(),
+                    description,
+                    columns,
+                },
+                _ => Object::Table {
+                    id,
+                    score: None,
+                    namespace: Some(namespace.clone()),
+                    name: name.clone(),
+                    description,
+                    columns,
+                    foreign_keys: Vec::new(),
+                    primary_key: None,
+                    unique_keys: Vec::new(),
+                    indexes: Vec::new(),
+                },
+            });
+
+        entries.insert((namespace, name), (id, column_names));
+    }
+
+    keys(catalog, &mut schema, &entries)?;
+    indexes(catalog, &mut schema, &entries)?;
+    foreign_keys(catalog, &mut schema, &entries)?;
+    routines(catalog, &mut schema)?;
+
+    Ok(schema)
+}
+
+/// Gets a fingerprint of the schema of a PostgreSQL database.
+///
+/// Uses the number of catalog rows describing the relations, columns, constraints, indexes,
+/// comments and routines in the schemas on the search path, and the latest transaction to write
+/// each kind of row. Adding or removing a row changes its count, and updating one writes a new
+/// version of it with a later transaction ID.
+#[tracing::instrument(skip_all, err)]
+pub(super) fn fingerprint(catalog: &dyn Catalog) -> anyhow::Result<Option<String>> {
+    // Aggregating in the database avoids sending every catalog row to be hashed.
+    const QUERY: &str = r#"
+            SELECT string_agg(concat_ws(':', "F"."count", "F"."xmin"), ',' ORDER BY "F"."catalog")
+            FROM (
+                SELECT 1 AS "catalog", count(*) AS "count", max("C"."xmin"::text::bigint) AS "xmin"
+                FROM
+                    "pg_catalog"."pg_class" AS "C"
+                    JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "C"."relnamespace"
+                WHERE "N"."nspname" = ANY (current_schemas(false))
+                UNION ALL
+                SELECT 2, count(*), max("A"."xmin"::text::bigint)
+                FROM
+                    "pg_catalog"."pg_attribute" AS "A"
+                    JOIN "pg_catalog"."pg_class" AS "C" ON "C"."oid" = "A"."attrelid"
+                    JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "C"."relnamespace"
+                WHERE "N"."nspname" = ANY (current_schemas(false)) AND "A"."attnum" > 0
+                UNION ALL
+                SELECT 3, count(*), max("K"."xmin"::text::bigint)
+                FROM
+                    "pg_catalog"."pg_constraint" AS "K"
+                    JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "K"."connamespace"
+                WHERE "N"."nspname" = ANY (current_schemas(false))
+                UNION ALL
+                SELECT 4, count(*), max("X"."xmin"::text::bigint)
+                FROM
+                    "pg_catalog"."pg_index" AS "X"
+                    JOIN "pg_catalog"."pg_class" AS "C" ON "C"."oid" = "X"."indrelid"
+                    JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "C"."relnamespace"
+                WHERE "N"."nspname" = ANY (current_schemas(false))
+                UNION ALL
+                SELECT 5, count(*), max("D"."xmin"::text::bigint)
+                FROM
+                    "pg_catalog"."pg_description" AS "D"
+                    LEFT JOIN "pg_catalog"."pg_class" AS "C"
+                        ON "D"."classoid" = 'pg_catalog.pg_class'::regclass AND "C"."oid" = "D"."objoid"
+                    LEFT JOIN "pg_catalog"."pg_proc" AS "P"
+                        ON "D"."classoid" = 'pg_catalog.pg_proc'::regclass AND "P"."oid" = "D"."objoid"
+                    JOIN "pg_catalog"."pg_namespace" AS "N"
+                        ON "N"."oid" = coalesce("C"."relnamespace", "P"."pronamespace")
+                WHERE "N"."nspname" = ANY (current_schemas(false))
+                UNION ALL
+                SELECT 6, count(*), max("P"."xmin"::text::bigint)
+                FROM
+                    "pg_catalog"."pg_proc" AS "P"
+                    JOIN "pg_catalog"."pg_namespace" AS "N" ON "N"."oid" = "P"."pronamespace"
+                WHERE "N"."nspname" = ANY (current_schemas(false))
+            ) AS "F"
+        "#;
+
+    let rows = catalog.query(QUERY)?;
+    Ok(rows.first().and_then(|row| row.optional(0)))
+}
+
+impl Database for Postgres {
+    fn schema(&self) -> anyhow::Result<Schema> {
+        schema(self)
+    }
+
+    fn fingerprint(&self) -> anyhow::Result<Option<String>> {
+        fingerprint(self)
+    }
+
+    fn dialect(&self) -> &dyn SqlDialect {
+        &PostgresDialect::NATIVE
+    }
+
+    #[tracing::instrument(skip(self, sink), err)]
+    fn execute(
+        &self,
+        sql: &str,
+        params: &[SqlParam],
+        sink: &mut dyn RowSink,
+    ) -> anyhow::Result<()> {
+        let mut client = self.client.borrow_mut();
+        let statement = client.prepare(sql)?;
+        let columns = statement
+            .columns()
+            .iter()
+            .map(|c| ColumnInfo {
+                name: c.name().to_string(),
+            })
+            .collect::<Vec<_>>();
+        sink.columns(&columns)?;
+
+        let mut values = Vec::with_capacity(columns.len());
+        let mut rows = client.query_raw(&statement, params)?;
+        while let Some(row) = rows.next()? {
+            values.clear();
+            for i in 0..columns.len() {
+                values.push(row.try_get::<_, Option<Text>>(i)?.map(|t| t.0));
+            }
+
+            let record = values.iter().map(Option::as_deref).collect::<Vec<_>>();
+            sink.row(&record)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Connects to a PostgreSQL database.
+///
+/// The connection string may either be a URL (`postgresql://user@host/db`) or a list of
+/// `key=value` pairs. TLS is not supported.
+pub fn connect(connection_string: &str) -> anyhow::Result<Box<dyn Database>> {
+    let client = Client::connect(connection_string, NoTls)?;
+    Ok(Box::new(Postgres {
+        client: RefCell::new(client),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alg::Name, schema::builder::ObjectBuilder};
+
+    /// Objects created in the schema of each integration test.
+    const FIXTURE: &str = r#"
+        CREATE TABLE "users" (
+            "id" integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+            "name" text NOT NULL UNIQUE
+        );
+        CREATE INDEX "users_name_a" ON "users" ("name") WHERE "name" < 'b';
+        CREATE TABLE "orders" (
+            "id" serial PRIMARY KEY,
+            "user_id" integer REFERENCES "users",
+            "total" numeric(10, 2) NOT NULL DEFAULT 0
+        );
+        CREATE VIEW "big_orders" AS SELECT * FROM "orders" WHERE "total" > 100;
+        CREATE FUNCTION "order_total"("order_id" integer) RETURNS numeric
+            AS 'SELECT "total" FROM "orders" WHERE "id" = $1' LANGUAGE sql;
+        CREATE FUNCTION "order_total"(integer, "tax" numeric) RETURNS numeric
+            AS 'SELECT "total" * (1 + $2) FROM "orders" WHERE "id" = $1' LANGUAGE sql;
+        COMMENT ON FUNCTION "order_total"(integer) IS 'Total of an order';
+        CREATE FUNCTION "user_orders"("uid" integer) RETURNS SETOF "orders"
+            AS 'SELECT * FROM "orders" WHERE "user_id" = $1' LANGUAGE sql;
+        CREATE FUNCTION "order_counts"(OUT "user_id" integer, OUT "count" bigint)
+            RETURNS SETOF record
+            AS 'SELECT "user_id", count(*) FROM "orders" GROUP BY 1' LANGUAGE sql;
+        INSERT INTO "users" ("name") VALUES ('alice'), ('bob');
+        INSERT INTO "orders" VALUES (1, 1, 250), (2, 2, 10);
+    "#;
+
+    /// A PostgreSQL database with a schema of test objects, which is dropped once the test ends.
+    struct TestDatabase {
+        db: Postgres,
+        namespace: String,
+    }
+
+    impl TestDatabase {
+        /// Connects to the database named by the `SQ_TEST_POSTGRES_URL` environment variable and
+        /// creates the test objects in a new schema, which is the only one on the search path.
+        ///
+        /// Returns [`None`] if the environment variable is not set.
+        fn new(name: &str) -> Option<TestDatabase> {
+            let url = std::env::var("SQ_TEST_POSTGRES_URL").ok()?;
+            let namespace = format!("sq_test_{}_{}", name, std::process::id());
+            let mut client = Client::connect(&url, NoTls).unwrap();
+            client
+                .batch_execute(&format!(
+                    r#"
+                    DROP SCHEMA IF EXISTS "{0}" CASCADE;
+                    CREATE SCHEMA "{0}";
+                    SET search_path TO "{0}";
+                    {1}
+                    "#,
+                    namespace, FIXTURE
+                ))
+                .unwrap();
+
+            Some(TestDatabase {
+                db: Postgres {
+                    client: RefCell::new(client),
+                },
+                namespace,
+            })
+        }
+
+        fn batch_execute(&self, sql: &str) {
+            self.db.client.borrow_mut().batch_execute(sql).unwrap();
+        }
+    }
+
+    impl Drop for TestDatabase {
+        fn drop(&mut self) {
+            let sql = format!(r#"DROP SCHEMA IF EXISTS "{}" CASCADE"#, self.namespace);
+            let _ = self.db.client.borrow_mut().batch_execute(&sql);
+        }
+    }
+
+    /// Collects the rows of a result as text.
+    #[derive(Default)]
+    struct Rows {
+        columns: Vec<String>,
+        rows: Vec<Vec<Option<String>>>,
+    }
+
+    impl RowSink for Rows {
+        fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+            self.columns = columns.iter().map(|c| c.name.clone()).collect();
+            Ok(())
+        }
+
+        fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+            self.rows
+                .push(row.iter().map(|v| v.map(String::from)).collect());
+            Ok(())
+        }
+    }
+
+    /// A catalog which answers every query with the same rows.
+    struct FixedCatalog(Vec<Vec<&'static str>>);
+
+    impl Catalog for FixedCatalog {
+        fn query(&self, _: &str) -> anyhow::Result<Vec<CatalogRow>> {
+            Ok(self
+                .0
+                .iter()
+                .map(|row| CatalogRow(row.iter().map(|v| Some(v.to_string())).collect()))
+                .collect())
+        }
+    }
+
+    /// Indexes the objects in `schema` as if they were all in the `public` schema.
+    fn object_index(schema: &Schema) -> ObjectIndex {
+        schema
+            .objects
+            .values()
+            .map(|obj| {
+                let columns = obj
+                    .columns()
+                    .iter()
+                    .map(|id| (schema.columns[*id].name.clone(), *id))
+                    .collect();
+                (
+                    ("public".to_string(), obj.name().to_string()),
+                    (obj.id(), columns),
+                )
+            })
+            .collect()
+    }
+
+    fn find<'a>(schema: &'a Schema, name: &str) -> &'a Object {
+        schema
+            .objects
+            .values()
+            .find(|obj| obj.name() == name)
+            .unwrap_or_else(|| panic!("missing object {}", name))
+    }
+
+    fn column_names(schema: &Schema, ids: &[ColumnId]) -> Vec<String> {
+        ids.iter()
+            .map(|id| schema.columns[*id].name.clone())
+            .collect()
+    }
+
+    #[test]
+    #[ignore = "requires SQ_TEST_POSTGRES_URL"]
+    fn test_schema_integration() {
+        let Some(test) = TestDatabase::new("schema") else {
+            return;
+        };
+
+        let schema = test.db.schema().unwrap();
+        assert_eq!(schema.objects.len(), 7);
+
+        let users = find(&schema, "users");
+        assert_eq!(users.namespace(), Some(test.namespace.as_str()));
+        assert_eq!(column_names(&schema, users.columns()), ["id", "name"]);
+        let pk = users.primary_key().unwrap();
+        assert_eq!(column_names(&schema, &pk.columns), ["id"]);
+        assert_eq!(users.unique_keys().len(), 1);
+        let partial = users
+            .indexes()
+            .iter()
+            .find(|index| index.name == "users_name_a")
+            .unwrap();
+        assert_eq!(partial.filter.as_deref(), Some("(name < 'b'::text)"));
+
+        let orders = find(&schema, "orders");
+        let [fk] = orders.foreign_keys() else {
+            panic!("expected one foreign key");
+        };
+        assert_eq!(fk.referenced_object, users.id());
+        assert_eq!(schema.columns[fk.column].name, "user_id");
+        assert_eq!(schema.columns[fk.referenced_column].name, "id");
+
+        let id = &schema.columns[orders.columns()[0]];
+        assert_eq!(
+            id.identity,
+            Some(Identity {
+                seed: Some(1),
+                increment: Some(1)
+            })
+        );
+
+        let total = &schema.columns[orders.columns()[2]];
+        assert_eq!(
+            total.data_type,
+            DataType::Decimal {
+                precision: Some(10),
+                scale: Some(2)
+            }
+        );
+        assert!(!total.nullable);
+
+        let big_orders = find(&schema, "big_orders");
+        assert!(matches!(big_orders, Object::View { .. }));
+        assert_eq!(big_orders.columns().len(), 3);
+    }
+
+    #[test]
+    #[ignore = "requires SQ_TEST_POSTGRES_URL"]
+    fn test_routines_integration() {
+        let Some(test) = TestDatabase::new("routines") else {
+            return;
+        };
+
+        let schema = test.db.schema().unwrap();
+        let mut overloads = schema
+            .objects
+            .values()
+            .filter(|obj| obj.name() == "order_total")
+            .collect::<Vec<_>>();
+        overloads.sort_by_key(|obj| obj.parameters().len());
+        let [single, taxed] = overloads.as_slice() else {
+            panic!("expected two overloads: {:?}", overloads);
+        };
+
+        let parameter_names = |obj: &Object| {
+            obj.parameters()
+                .iter()
+                .map(|p| schema.columns[p.column].name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parameter_names(single), ["order_id"]);
+        assert_eq!(parameter_names(taxed), ["$1", "tax"]);
+        assert_eq!(single.description(), Some("Total of an order"));
+        assert!(matches!(
+            single,
+            Object::Function {
+                return_type: Some(DataType::Decimal { .. }),
+                ..
+            }
+        ));
+
+        let counts = find(&schema, "order_counts");
+        assert!(counts.parameters().is_empty());
+        assert_eq!(
+            column_names(&schema, counts.columns()),
+            ["user_id", "count"]
+        );
+
+        let user_orders = find(&schema, "user_orders");
+        assert_eq!(parameter_names(user_orders), ["uid"]);
+        assert_eq!(
+            column_names(&schema, user_orders.columns()),
+            ["id", "user_id", "total"]
+        );
+        assert!(matches!(
+            user_orders,
+            Object::Function {
+                return_type: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[ignore = "requires SQ_TEST_POSTGRES_URL"]
+    fn test_execute_integration() {
+        let Some(test) = TestDatabase::new("execute") else {
+            return;
+        };
+
+        let mut rows = Rows::default();
+        test.db
+            .execute(
+                r#"SELECT "id", "name" FROM "users" WHERE "id" = $1"#,
+                &[SqlParam::String("2".to_string())],
+                &mut rows,
+            )
+            .unwrap();
+
+        assert_eq!(rows.columns, ["id", "name"]);
+        assert_eq!(
+            rows.rows,
+            [[Some("2".to_string()), Some("bob".to_string())]]
+        );
+    }
+
+    #[test]
+    #[ignore = "requires SQ_TEST_POSTGRES_URL"]
+    fn test_fingerprint_integration() {
+        let Some(test) = TestDatabase::new("fingerprint") else {
+            return;
+        };
+
+        let mut fingerprint = test.db.fingerprint().unwrap();
+        assert!(fingerprint.is_some());
+        assert_eq!(test.db.fingerprint().unwrap(), fingerprint);
+
+        for statement in [
+            r#"ALTER TABLE "orders" ADD COLUMN "note" text"#,
+            r#"ALTER TABLE "orders" DROP CONSTRAINT "orders_user_id_fkey""#,
+            r#"COMMENT ON TABLE "orders" IS 'Orders placed by users'"#,
+            r#"DROP VIEW "big_orders""#,
+        ] {
+            test.batch_execute(statement);
+            let changed = test.db.fingerprint().unwrap();
+            assert_ne!(changed, fingerprint, "{}", statement);
+            fingerprint = changed;
+        }
+    }
+
+    #[test]
+    fn test_foreign_keys_with_unresolved_column() {
+        let mut schema = Schema::default();
+        let regions = ObjectBuilder::new("regions")
+            .columns(&["country", "code"])
+            .table(&mut schema);
+        let offices = ObjectBuilder::new("offices")
+            .columns(&["country", "code", "region_country"])
+            .table(&mut schema);
+        let objects = object_index(&schema);
+
+        let catalog = FixedCatalog(vec![
+            vec![
+                "public",
+                "offices",
+                "country",
+                "public",
+                "regions",
+                "country",
+                "fk_region",
+            ],
+            vec![
+                "public",
+                "offices",
+                "code",
+                "public",
+                "regions",
+                "missing",
+                "fk_region",
+            ],
+            vec![
+                "public",
+                "offices",
+                "region_country",
+                "public",
+                "regions",
+                "country",
+                "fk_country",
+            ],
+        ]);
+        foreign_keys(&catalog, &mut schema, &objects).unwrap();
+
+        let fks = schema.objects[offices].foreign_keys();
+        assert_eq!(fks.len(), 1);
+        assert_eq!(fks[0].constraint.as_deref(), Some("fk_country"));
+        assert_eq!(fks[0].referenced_object, regions);
+    }
+
+    #[test]
+    fn test_indexes_with_unresolved_column() {
+        let mut schema = Schema::default();
+        let offices = ObjectBuilder::new("offices")
+            .columns(&["country", "code"])
+            .table(&mut schema);
+        let objects = object_index(&schema);
+
+        let catalog = FixedCatalog(vec![
+            vec![
+                "public",
+                "offices",
+                "ix_country",
+                "false",
+                "false",
+                "country",
+                "false",
+            ],
+            vec![
+                "public",
+                "offices",
+                "ix_country",
+                "false",
+                "false",
+                "code",
+                "true",
+            ],
+            vec![
+                "public",
+                "offices",
+                "ix_legacy",
+                "false",
+                "false",
+                "country",
+                "false",
+            ],
+            vec![
+                "public",
+                "offices",
+                "ix_legacy",
+                "false",
+                "false",
+                "legacy",
+                "false",
+            ],
+        ]);
+        indexes(&catalog, &mut schema, &objects).unwrap();
+
+        let Object::Table { indexes, .. } = &schema.objects[offices] else {
+            panic!("offices is not a table");
+        };
+        assert_eq!(indexes.len(), 1);
+        assert_eq!(indexes[0].name, "ix_country");
+        assert_eq!(column_names(&schema, &indexes[0].columns), ["country"]);
+        assert_eq!(
+            column_names(&schema, &indexes[0].included_columns),
+            ["code"]
+        );
+    }
+
+    #[test]
+    fn test_params_are_sent_as_text() {
+        let mut out = BytesMut::new();
+        let param = SqlParam::Int(-42);
+        assert!(matches!(param.encode_format(&Type::NUMERIC), Format::Text));
+        assert!(matches!(
+            param.to_sql_checked(&Type::NUMERIC, &mut out),
+            Ok(IsNull::No)
+        ));
+        assert_eq!(&out[..], b"-42");
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let decode = |ty: &Type, raw: &[u8]| Text::from_sql(ty, raw).unwrap().0;
+        assert_eq!(decode(&Type::TEXT, b"hello"), "hello");
+        assert_eq!(decode(&Type::INT4, &7i32.to_be_bytes()), "7");
+        assert_eq!(decode(&Type::BOOL, &[1]), "true");
+        assert!(!<Text as FromSql>::accepts(&Type::TIMESTAMP));
+    }
+
+    #[test]
+    fn test_data_type() {
+        assert_eq!(
+            data_type("character varying", "varchar", Some(50), None, None),
+            DataType::VarChar(Some(50))
+        );
+        assert_eq!(
+            data_type("ARRAY", "_int4", None, None, None),
+            DataType::Other("_int4".to_string())
+        );
+    }
+}
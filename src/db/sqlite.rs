@@ -1,17 +1,589 @@
-use std::path::Path;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
-use rusqlite::Connection;
+use rusqlite::{
+    Connection, ToSql, params_from_iter,
+    types::{ToSqlOutput, ValueRef},
+};
 
-use crate::db::Database;
+use crate::{
+    db::{ColumnInfo, Database, RowSink},
+    schema::{
+        Column, ColumnId, DataType, ForeignKey, Identity, Index, Key, Object, ObjectId, Schema,
+    },
+    sql::{SqlDialect, SqlParam, SqliteDialect},
+};
 
 struct Sqlite {
-    #[allow(dead_code)]
     conn: Connection,
 }
 
+/// Converts a SQLite value into its textual representation.
+fn value_to_string(value: ValueRef<'_>) -> Option<String> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(i) => Some(i.to_string()),
+        ValueRef::Real(f) => Some(f.to_string()),
+        ValueRef::Text(t) => Some(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => Some(b.iter().fold(String::from("0x"), |mut s, byte| {
+            s.push_str(&format!("{:02X}", byte));
+            s
+        })),
+    }
+}
+
+impl ToSql for SqlParam {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            SqlParam::String(s) => ToSqlOutput::from(s.as_str()),
+            SqlParam::Int(i) => ToSqlOutput::from(*i),
+        })
+    }
+}
+
+/// Maps a declared SQLite column type to a [`DataType`].
+///
+/// SQLite allows any type name to be declared, optionally followed by a length or a precision and
+/// scale in parentheses. Names which are not recognised are mapped following SQLite's own type
+/// affinity rules.
+fn data_type(declared: &str) -> DataType {
+    let (name, args) = match declared.split_once('(') {
+        Some((name, args)) => (name, args.trim_end_matches(')')),
+        None => (declared, ""),
+    };
+
+    let mut args = args.split(',').map(|arg| arg.trim().parse::<u32>().ok());
+    let first = args.next().flatten();
+    let second = args.next().flatten();
+
+    let name = name.trim();
+    match DataType::from_name(name, first, first, second) {
+        DataType::Other(_) => {
+            let upper = name.to_ascii_uppercase();
+            if upper.contains("INT") {
+                DataType::Integer
+            } else if ["CHAR", "CLOB", "TEXT"].iter().any(|s| upper.contains(s)) {
+                DataType::Text
+            } else if upper.contains("BLOB") {
+                DataType::Blob
+            } else if ["REAL", "FLOA", "DOUB"].iter().any(|s| upper.contains(s)) {
+                DataType::Double
+            } else {
+                DataType::Other(name.to_string())
+            }
+        }
+        data_type => data_type,
+    }
+}
+
+/// Iterates over the characters of a SQL fragment which are not inside a quoted string or
+/// identifier, along with their byte offsets.
+fn unquoted(sql: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quote = None;
+    sql.char_indices().filter(move |&(_, c)| match quote {
+        Some(q) => {
+            if c == q {
+                quote = None;
+            }
+            false
+        }
+        None => {
+            quote = match c {
+                '\'' | '"' | '`' => Some(c),
+                '[' => Some(']'),
+                _ => None,
+            };
+            quote.is_none()
+        }
+    })
+}
+
+/// Splits the body of a `CREATE TABLE` statement into its column definitions and table
+/// constraints, in the order they were declared.
+fn table_definitions(sql: &str) -> Vec<&str> {
+    let Some((start, _)) = unquoted(sql).find(|&(_, c)| c == '(') else {
+        return Vec::new();
+    };
+
+    let body = &sql[start + 1..];
+    let mut definitions = Vec::new();
+    let mut depth = 0;
+    let mut last = 0;
+    for (i, c) in unquoted(body) {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                definitions.push(body[last..i].trim());
+                break;
+            }
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                definitions.push(body[last..i].trim());
+                last = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    definitions
+}
+
+/// Extracts the expression of a generated column from its definition, which follows the `AS`
+/// keyword in parentheses.
+fn generated_expression(definition: &str) -> Option<&str> {
+    let bytes = definition.as_bytes();
+    let mut depth = 0;
+    for (i, c) in unquoted(definition) {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            'a' | 'A' if depth == 0 && i > 0 => {
+                let is_keyword = bytes[i - 1].is_ascii_whitespace()
+                    && bytes.len() > i + 2
+                    && bytes[i..i + 2].eq_ignore_ascii_case(b"as")
+                    && (bytes[i + 2].is_ascii_whitespace() || bytes[i + 2] == b'(');
+                if !is_keyword {
+                    continue;
+                }
+
+                let rest = definition[i + 2..].trim_start();
+                if !rest.starts_with('(') {
+                    continue;
+                }
+
+                let mut depth = 0;
+                for (j, c) in unquoted(rest) {
+                    match c {
+                        '(' => depth += 1,
+                        ')' if depth == 1 => return Some(rest[1..j].trim()),
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                }
+                return None;
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Extracts the condition of a partial index from its `CREATE INDEX` statement, which follows the
+/// `WHERE` keyword after the list of indexed columns.
+fn index_filter(sql: &str) -> Option<&str> {
+    let mut depth = 0;
+    let (end, _) = unquoted(sql).find(|&(_, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => return false,
+        }
+        depth == 0
+    })?;
+
+    let rest = sql[end + 1..].trim_start();
+    let keyword = rest.get(..5)?;
+    let condition = rest[5..].trim();
+    (keyword.eq_ignore_ascii_case("where") && !condition.is_empty()).then_some(condition)
+}
+
+/// An object found while fetching the schema, used to resolve foreign key references by name.
+struct ObjectEntry {
+    id: ObjectId,
+    columns: HashMap<String, ColumnId>,
+    primary_key: Vec<ColumnId>,
+}
+
+impl Sqlite {
+    /// Fetches the columns of a table or view, returning them along with the table's primary key
+    /// columns.
+    ///
+    /// The expressions of generated columns are not exposed by any pragma, so they are read from
+    /// the `CREATE TABLE` statement in `sql`.
+    fn columns(
+        &self,
+        schema: &mut Schema,
+        object_name: &str,
+        sql: &str,
+    ) -> anyhow::Result<(Vec<ColumnId>, Vec<ColumnId>)> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT "cid", "name", "type", "notnull", "dflt_value", "pk", "hidden"
+            FROM pragma_table_xinfo(?1)
+            ORDER BY "cid"
+            "#,
+        )?;
+
+        let definitions = table_definitions(sql);
+        let mut columns = Vec::new();
+        let mut primary_key = Vec::new();
+        let mut rows = stmt.query([object_name])?;
+        while let Some(row) = rows.next()? {
+            let cid: usize = row.get(0)?;
+            let name: String = row.get(1)?;
+            let declared: String = row.get(2)?;
+            let not_null: bool = row.get(3)?;
+            let default: Option<String> = row.get(4)?;
+            let pk: i64 = row.get(5)?;
+            // Hidden columns of virtual tables are 1, and virtual or stored generated columns are
+            // 2 or 3 respectively.
+            let hidden: i64 = row.get(6)?;
+
+            if hidden == 1 {
+                continue;
+            }
+
+            let computed = match hidden {
+                2 | 3 => definitions
+                    .get(cid)
+                    .and_then(|definition| generated_expression(definition))
+                    .map(str::to_string),
+                _ => None,
+            };
+
+            tracing::debug!("Found column: {}", name);
+            let column_id = schema.columns.insert_with_key(|id| Column {
+                id,
+                score: None,
+                name,
+                data_type: data_type(&declared),
+                nullable: !not_null,
+                default,
+                identity: None,
+                computed,
+                description: None,
+            });
+
+            columns.push(column_id);
+            if pk > 0 {
+                primary_key.push((pk, column_id, declared));
+            }
+        }
+
+        // A single `INTEGER PRIMARY KEY` column is an alias for the rowid, which is assigned
+        // automatically unless the table is declared `WITHOUT ROWID`.
+        let without_rowid = sql
+            .rfind(')')
+            .is_some_and(|end| sql[end..].to_ascii_uppercase().contains("WITHOUT"));
+        if let [(_, column_id, declared)] = primary_key.as_slice()
+            && declared.eq_ignore_ascii_case("INTEGER")
+            && !without_rowid
+        {
+            schema.columns[*column_id].identity = Some(Identity {
+                seed: None,
+                increment: None,
+            });
+        }
+
+        primary_key.sort_by_key(|(pk, ..)| *pk);
+        let primary_key = primary_key.into_iter().map(|(_, id, _)| id).collect();
+        Ok((columns, primary_key))
+    }
+
+    /// Fetches the indexes on a table, given its columns indexed by lowercase name, along with
+    /// the origin of each index.
+    ///
+    /// Indexes created for `UNIQUE` constraints have an origin of 'u', as opposed to 'c' for those
+    /// created by `CREATE INDEX` or 'pk' for primary keys. Indexes on expressions are skipped. The
+    /// condition of a partial index is only available from the statement which created it.
+    fn indexes(
+        &self,
+        object_name: &str,
+        columns: &HashMap<String, ColumnId>,
+    ) -> anyhow::Result<Vec<(String, Index)>> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT "name", "unique", "origin", "partial" FROM pragma_index_list(?1) ORDER BY "seq""#,
+        )?;
+        let list = stmt
+            .query_map([object_name], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, bool>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, bool>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self
+            .conn
+            .prepare(r#"SELECT "name" FROM pragma_index_info(?1) ORDER BY "seqno""#)?;
+        let mut sql_stmt = self.conn.prepare(
+            r#"SELECT "sql" FROM "sqlite_master" WHERE "type" = 'index' AND "name" = ?1"#,
+        )?;
+
+        let mut indexes = Vec::new();
+        for (name, unique, origin, partial) in list {
+            let names = stmt
+                .query_map([&name], |row| row.get::<_, Option<String>>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let index_columns = names
+                .iter()
+                .map(|name| columns.get(&name.as_deref()?.to_lowercase()).copied())
+                .collect::<Option<Vec<_>>>();
+
+            let Some(index_columns) = index_columns else {
+                tracing::warn!("Unable to resolve index: {}", name);
+                continue;
+            };
+
+            let filter = if partial {
+                let sql = sql_stmt.query_row([&name], |row| row.get::<_, String>(0))?;
+                index_filter(&sql).map(str::to_string)
+            } else {
+                None
+            };
+
+            tracing::debug!("Found index: {}", name);
+            indexes.push((
+                origin,
+                Index {
+                    name,
+                    columns: index_columns,
+                    included_columns: Vec::new(),
+                    unique,
+                    // Tables are stored in rowid order rather than the order of any index.
+                    clustered: false,
+                    filter,
+                },
+            ));
+        }
+
+        Ok(indexes)
+    }
+
+    /// Fetches the foreign keys declared on a table.
+    ///
+    /// A foreign key is discarded entirely if any of its columns can't be resolved, as joining on
+    /// only some of the columns of a composite foreign key would match the wrong rows.
+    fn foreign_keys(
+        &self,
+        object_name: &str,
+        objects: &HashMap<String, ObjectEntry>,
+    ) -> anyhow::Result<Vec<ForeignKey>> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT "id", "seq", "table", "from", "to" FROM pragma_foreign_key_list(?1) ORDER BY "id", "seq""#,
+        )?;
+
+        let Some(object) = objects.get(&object_name.to_lowercase()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut foreign_keys = Vec::new();
+        let mut unresolved = HashSet::new();
+        let mut rows = stmt.query([object_name])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let seq: usize = row.get(1)?;
+            let referenced_table: String = row.get(2)?;
+            let from: String = row.get(3)?;
+            let to: Option<String> = row.get(4)?;
+
+            let Some(referenced) = objects.get(&referenced_table.to_lowercase()) else {
+                tracing::warn!("Unknown table in foreign key: {}", referenced_table);
+                unresolved.insert(id);
+                continue;
+            };
+
+            let Some(&column) = object.columns.get(&from.to_lowercase()) else {
+                tracing::warn!("Unknown column in foreign key: {}", from);
+                unresolved.insert(id);
+                continue;
+            };
+
+            // A foreign key without target columns references the primary key of the other
+            // table, column by column.
+            let referenced_column = match to {
+                Some(to) => referenced.columns.get(&to.to_lowercase()).copied(),
+                None => referenced.primary_key.get(seq).copied(),
+            };
+
+            let Some(referenced_column) = referenced_column else {
+                tracing::warn!(
+                    "Unable to resolve referenced column in foreign key: {}.{}",
+                    object_name,
+                    from
+                );
+                unresolved.insert(id);
+                continue;
+            };
+
+            tracing::debug!(
+                "Found foreign key: {}.{} -> {}",
+                object_name,
+                from,
+                referenced_table
+            );
+            // SQLite does not expose the names of constraints, but the ID from the pragma
+            // identifies the foreign key within the table.
+            foreign_keys.push((
+                id,
+                ForeignKey {
+                    constraint: Some(id.to_string()),
+                    column,
+                    referenced_object: referenced.id,
+                    referenced_column,
+                },
+            ));
+        }
+
+        Ok(foreign_keys
+            .into_iter()
+            .filter(|(id, _)| !unresolved.contains(id))
+            .map(|(_, fk)| fk)
+            .collect())
+    }
+}
+
 impl Database for Sqlite {
-    fn schema(&self) -> anyhow::Result<crate::schema::Schema> {
-        todo!()
+    #[tracing::instrument(skip_all, err)]
+    fn schema(&self) -> anyhow::Result<Schema> {
+        tracing::info!("Fetching SQLite database schema");
+        let mut schema = Schema::default();
+
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT "name", "type", "sql"
+            FROM "sqlite_master"
+            WHERE "type" IN ('table', 'view') AND "name" NOT LIKE 'sqlite\_%' ESCAPE '\'
+            ORDER BY "name"
+            "#,
+        )?;
+
+        let objects = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Create every object before resolving foreign keys, since a foreign key may reference an
+        // object which has not been seen yet.
+        let mut entries = HashMap::new();
+        for (name, object_type, sql) in &objects {
+            let (columns, primary_key) = self.columns(&mut schema, name, sql)?;
+            let column_names = columns
+                .iter()
+                .map(|id| (schema.columns[*id].name.to_lowercase(), *id))
+                .collect();
+
+            let indexes = match object_type.as_str() {
+                "table" => self.indexes(name, &column_names)?,
+                _ => Vec::new(),
+            };
+
+            let unique_keys = indexes
+                .iter()
+                .filter(|(origin, _)| origin == "u")
+                .map(|(_, index)| Key {
+                    name: None,
+                    columns: index.columns.clone(),
+                })
+                .collect();
+
+            let id = schema
+                .objects
+                .insert_with_key(|id| match object_type.as_str() {
+                    "view" => Object::View {
+                        id,
+                        score: None,
+                        namespace: None,
+                        name: name.clone(),
+                        description: None,
+                        columns,
+                    },
+                    _ => Object::Table {
+                        id,
+                        score: None,
+                        namespace: None,
+                        name: name.clone(),
+                        description: None,
+                        columns,
+                        foreign_keys: Vec::new(),
+                        primary_key: (!primary_key.is_empty()).then(|| Key {
+                            name: None,
+                            columns: primary_key.clone(),
+                        }),
+                        unique_keys,
+                        indexes: indexes.into_iter().map(|(_, index)| index).collect(),
+                    },
+                });
+
+            tracing::debug!("Found {}: {}", object_type, name);
+            entries.insert(
+                name.to_lowercase(),
+                ObjectEntry {
+                    id,
+                    columns: column_names,
+                    primary_key,
+                },
+            );
+        }
+
+        for (name, object_type, _) in &objects {
+            if object_type != "table" {
+                continue;
+            }
+
+            let fks = self.foreign_keys(name, &entries)?;
+            let id = entries[&name.to_lowercase()].id;
+            if let Some(Object::Table { foreign_keys, .. }) = schema.objects.get_mut(id) {
+                *foreign_keys = fks;
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// Uses the schema version, which SQLite increments whenever the schema is changed.
+    fn fingerprint(&self) -> anyhow::Result<Option<String>> {
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA schema_version", [], |row| row.get(0))?;
+        Ok(Some(version.to_string()))
+    }
+
+    fn dialect(&self) -> &dyn SqlDialect {
+        &SqliteDialect
+    }
+
+    #[tracing::instrument(skip(self, sink), err)]
+    fn execute(
+        &self,
+        sql: &str,
+        params: &[SqlParam],
+        sink: &mut dyn RowSink,
+    ) -> anyhow::Result<()> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let columns = stmt
+            .column_names()
+            .into_iter()
+            .map(|name| ColumnInfo {
+                name: name.to_string(),
+            })
+            .collect::<Vec<_>>();
+        sink.columns(&columns)?;
+
+        let mut values = Vec::with_capacity(columns.len());
+        let mut rows = stmt.query(params_from_iter(params))?;
+        while let Some(row) = rows.next()? {
+            values.clear();
+            for i in 0..columns.len() {
+                values.push(value_to_string(row.get_ref(i)?));
+            }
+
+            let record = values.iter().map(Option::as_deref).collect::<Vec<_>>();
+            sink.row(&record)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -20,3 +592,315 @@ pub fn connect(file: &Path) -> anyhow::Result<Box<dyn Database>> {
     let conn = Connection::open(file)?;
     Ok(Box::new(Sqlite { conn }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alg::Name;
+
+    fn fixture() -> Sqlite {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE users (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                name_length INTEGER GENERATED ALWAYS AS (length(name)) VIRTUAL,
+                UNIQUE (name)
+            );
+
+            CREATE TABLE posts (
+                id INTEGER PRIMARY KEY,
+                user_id INTEGER REFERENCES users,
+                editor_id INTEGER,
+                title VARCHAR(100),
+                FOREIGN KEY (editor_id) REFERENCES users (id)
+            );
+
+            CREATE INDEX ix_posts_user_title ON posts (user_id, title);
+
+            CREATE VIEW post_titles AS SELECT title FROM posts;
+            "#,
+        )
+        .unwrap();
+
+        Sqlite { conn }
+    }
+
+    fn object<'a>(schema: &'a Schema, name: &str) -> &'a Object {
+        schema.objects.values().find(|o| o.name() == name).unwrap()
+    }
+
+    fn column_names(schema: &Schema, obj: &Object) -> Vec<String> {
+        obj.columns()
+            .iter()
+            .map(|id| schema.columns[*id].name.clone())
+            .collect()
+    }
+
+    #[derive(Default)]
+    struct Collect {
+        columns: Vec<String>,
+        rows: Vec<Vec<Option<String>>>,
+    }
+
+    impl RowSink for Collect {
+        fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+            self.columns = columns.iter().map(|c| c.name.clone()).collect();
+            Ok(())
+        }
+
+        fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+            self.rows
+                .push(row.iter().map(|v| v.map(String::from)).collect());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let db = fixture();
+        db.conn
+            .execute_batch(
+                r#"
+                INSERT INTO users (id, name) VALUES (1, 'alice'), (2, 'bob');
+                INSERT INTO posts (id, user_id, title) VALUES (10, 2, NULL);
+                "#,
+            )
+            .unwrap();
+
+        let mut sink = Collect::default();
+        db.execute(
+            "SELECT u.name, p.id, p.title FROM users AS u JOIN posts AS p ON u.id = p.user_id \
+             WHERE u.name = ?1 AND p.id > ?2",
+            &[SqlParam::String("bob".to_string()), SqlParam::Int(5)],
+            &mut sink,
+        )
+        .unwrap();
+
+        assert_eq!(sink.columns, ["name", "id", "title"]);
+        assert_eq!(
+            sink.rows,
+            [vec![Some("bob".to_string()), Some("10".to_string()), None]]
+        );
+    }
+
+    #[test]
+    fn test_schema_objects() {
+        let schema = fixture().schema().unwrap();
+        let mut names = schema
+            .objects
+            .values()
+            .map(|o| o.name())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["post_titles", "posts", "users"]);
+
+        assert!(matches!(object(&schema, "users"), Object::Table { .. }));
+        assert!(matches!(
+            object(&schema, "post_titles"),
+            Object::View { .. }
+        ));
+    }
+
+    #[test]
+    fn test_schema_columns() {
+        let schema = fixture().schema().unwrap();
+        let posts = object(&schema, "posts");
+        assert_eq!(
+            column_names(&schema, posts),
+            ["id", "user_id", "editor_id", "title"]
+        );
+
+        let users = object(&schema, "users");
+        let name = &schema.columns[users.columns()[1]];
+        assert!(!name.nullable);
+
+        let user_id = &schema.columns[posts.columns()[1]];
+        assert!(user_id.nullable);
+        assert_eq!(user_id.data_type, DataType::Integer);
+
+        let title = &schema.columns[posts.columns()[3]];
+        assert_eq!(title.data_type, DataType::VarChar(Some(100)));
+    }
+
+    #[test]
+    fn test_schema_column_definitions() {
+        let schema = fixture().schema().unwrap();
+        let users = object(&schema, "users");
+        let [id, _, created_at, name_length] = users.columns() else {
+            panic!("unexpected columns: {:?}", column_names(&schema, users));
+        };
+
+        assert!(schema.columns[*id].identity.is_some());
+        assert_eq!(
+            schema.columns[*created_at].default.as_deref(),
+            Some("CURRENT_TIMESTAMP")
+        );
+        assert_eq!(
+            schema.columns[*name_length].computed.as_deref(),
+            Some("length(name)")
+        );
+
+        // Only a single `INTEGER PRIMARY KEY` column is an alias for the rowid.
+        let posts = object(&schema, "posts");
+        assert!(schema.columns[posts.columns()[0]].identity.is_some());
+        assert!(schema.columns[posts.columns()[1]].identity.is_none());
+    }
+
+    #[test]
+    fn test_generated_expression() {
+        let definitions = table_definitions(
+            r#"CREATE TABLE "t (x" (a INT, "as" TEXT AS (upper("as")) STORED, c AS(a * (a + 1)), CHECK (a > 0))"#,
+        );
+        assert_eq!(definitions.len(), 4);
+        assert_eq!(generated_expression(definitions[0]), None);
+        assert_eq!(generated_expression(definitions[1]), Some(r#"upper("as")"#));
+        assert_eq!(generated_expression(definitions[2]), Some("a * (a + 1)"));
+        assert_eq!(generated_expression(definitions[3]), None);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let db = fixture();
+        let before = db.fingerprint().unwrap();
+        assert!(before.is_some());
+        assert_eq!(db.fingerprint().unwrap(), before);
+
+        db.conn
+            .execute_batch("ALTER TABLE posts ADD COLUMN body TEXT")
+            .unwrap();
+        assert_ne!(db.fingerprint().unwrap(), before);
+    }
+
+    #[test]
+    fn test_data_type() {
+        assert_eq!(
+            data_type("DECIMAL(10, 2)"),
+            DataType::Decimal {
+                precision: Some(10),
+                scale: Some(2)
+            }
+        );
+        assert_eq!(data_type("UNSIGNED BIG INT"), DataType::Integer);
+        assert_eq!(data_type("VARYING CHARACTER(255)"), DataType::Text);
+        assert_eq!(data_type(""), DataType::Unknown);
+    }
+
+    #[test]
+    fn test_schema_keys() {
+        let schema = fixture().schema().unwrap();
+        let users = object(&schema, "users");
+        let key_names = |key: &Key| {
+            key.columns
+                .iter()
+                .map(|id| schema.columns[*id].name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(key_names(users.primary_key().unwrap()), ["id"]);
+        assert_eq!(
+            users
+                .unique_keys()
+                .iter()
+                .map(key_names)
+                .collect::<Vec<_>>(),
+            [["name"]]
+        );
+        assert!(object(&schema, "post_titles").primary_key().is_none());
+    }
+
+    #[test]
+    fn test_schema_indexes() {
+        let schema = fixture().schema().unwrap();
+        let posts = object(&schema, "posts");
+        let [index] = posts.indexes() else {
+            panic!("expected a single index: {:?}", posts.indexes());
+        };
+
+        assert_eq!(index.name, "ix_posts_user_title");
+        assert!(!index.unique);
+        assert_eq!(
+            index
+                .columns
+                .iter()
+                .map(|id| schema.columns[*id].name.as_str())
+                .collect::<Vec<_>>(),
+            ["user_id", "title"]
+        );
+
+        // The index backing the unique constraint on `users` is reported as well.
+        let users = object(&schema, "users");
+        assert!(users.indexes().iter().all(|index| index.unique));
+        assert_eq!(users.indexes().len(), 1);
+        assert!(users.indexes()[0].filter.is_none());
+    }
+
+    #[test]
+    fn test_schema_partial_index() {
+        let db = fixture();
+        db.conn
+            .execute_batch("CREATE INDEX ix_posts_open ON posts (user_id) WHERE title <> 'x)'")
+            .unwrap();
+
+        let schema = db.schema().unwrap();
+        let index = object(&schema, "posts")
+            .indexes()
+            .iter()
+            .find(|index| index.name == "ix_posts_open")
+            .unwrap();
+        assert_eq!(index.filter.as_deref(), Some("title <> 'x)'"));
+    }
+
+    #[test]
+    fn test_index_filter() {
+        assert_eq!(
+            index_filter(r#"CREATE INDEX "i (" ON t (a, (b + 1)) where a > 0"#),
+            Some("a > 0")
+        );
+        assert_eq!(index_filter("CREATE INDEX i ON t (a)"), None);
+    }
+
+    #[test]
+    fn test_schema_foreign_keys() {
+        let schema = fixture().schema().unwrap();
+        let users = object(&schema, "users");
+        let posts = object(&schema, "posts");
+
+        let mut fks = posts
+            .foreign_keys()
+            .iter()
+            .map(|fk| {
+                assert_eq!(fk.referenced_object, users.id());
+                (
+                    schema.columns[fk.column].name.as_str(),
+                    schema.columns[fk.referenced_column].name.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        fks.sort();
+        assert_eq!(fks, [("editor_id", "id"), ("user_id", "id")]);
+        assert_eq!(schema.foreign_objects(users.id()).count(), 1);
+    }
+
+    #[test]
+    fn test_schema_unresolved_foreign_key() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE regions (country TEXT, code TEXT, PRIMARY KEY (country, code));
+            CREATE TABLE offices (
+                country TEXT,
+                code TEXT,
+                FOREIGN KEY (country, code) REFERENCES regions (country, missing)
+            );
+            "#,
+        )
+        .unwrap();
+
+        let schema = Sqlite { conn }.schema().unwrap();
+        assert!(object(&schema, "offices").foreign_keys().is_empty());
+    }
+}
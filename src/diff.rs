@@ -0,0 +1,540 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    alg::Name,
+    schema::{Column, DataType, Object, ParameterMode, Schema},
+};
+
+/// A difference between two versions of a schema.
+///
+/// Objects are identified by their qualified name (along with their parameter types, for
+/// procedures and functions) and columns and parameters by their name within an object, as IDs
+/// are not stable between fetches of a schema.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Difference {
+    AddedObject {
+        kind: &'static str,
+        object: String,
+    },
+    RemovedObject {
+        kind: &'static str,
+        object: String,
+    },
+    ChangedKind {
+        object: String,
+        from: &'static str,
+        to: &'static str,
+    },
+    AddedColumn {
+        object: String,
+        column: String,
+    },
+    RemovedColumn {
+        object: String,
+        column: String,
+    },
+    ChangedType {
+        object: String,
+        column: String,
+        from: DataType,
+        to: DataType,
+    },
+    ChangedNullability {
+        object: String,
+        column: String,
+        nullable: bool,
+    },
+    AddedForeignKey {
+        object: String,
+        foreign_key: ForeignKeyName,
+    },
+    RemovedForeignKey {
+        object: String,
+        foreign_key: ForeignKeyName,
+    },
+    AddedParameter {
+        object: String,
+        parameter: String,
+    },
+    RemovedParameter {
+        object: String,
+        parameter: String,
+    },
+    ChangedParameterType {
+        object: String,
+        parameter: String,
+        from: DataType,
+        to: DataType,
+    },
+    ChangedParameterMode {
+        object: String,
+        parameter: String,
+        from: ParameterMode,
+        to: ParameterMode,
+    },
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::AddedObject { kind, object } => write!(f, "+ {} {}", kind, object),
+            Difference::RemovedObject { kind, object } => write!(f, "- {} {}", kind, object),
+            Difference::ChangedKind { object, from, to } => {
+                write!(f, "~ {} {} (was {})", to, object, from)
+            }
+            Difference::AddedColumn { object, column } => {
+                write!(f, "+ column {}.{}", object, column)
+            }
+            Difference::RemovedColumn { object, column } => {
+                write!(f, "- column {}.{}", object, column)
+            }
+            Difference::ChangedType {
+                object,
+                column,
+                from,
+                to,
+            } => write!(f, "~ column {}.{}: {} -> {}", object, column, from, to),
+            Difference::ChangedNullability {
+                object,
+                column,
+                nullable,
+            } => {
+                let (from, to) = if *nullable {
+                    ("not null", "null")
+                } else {
+                    ("null", "not null")
+                };
+                write!(f, "~ column {}.{}: {} -> {}", object, column, from, to)
+            }
+            Difference::AddedForeignKey {
+                object,
+                foreign_key,
+            } => write!(f, "+ foreign key {} {}", object, foreign_key),
+            Difference::RemovedForeignKey {
+                object,
+                foreign_key,
+            } => write!(f, "- foreign key {} {}", object, foreign_key),
+            Difference::AddedParameter { object, parameter } => {
+                write!(f, "+ parameter {} {}", object, parameter)
+            }
+            Difference::RemovedParameter { object, parameter } => {
+                write!(f, "- parameter {} {}", object, parameter)
+            }
+            Difference::ChangedParameterType {
+                object,
+                parameter,
+                from,
+                to,
+            } => write!(
+                f,
+                "~ parameter {} {}: {} -> {}",
+                object, parameter, from, to
+            ),
+            Difference::ChangedParameterMode {
+                object,
+                parameter,
+                from,
+                to,
+            } => write!(
+                f,
+                "~ parameter {} {}: {} -> {}",
+                object, parameter, from, to
+            ),
+        }
+    }
+}
+
+/// A foreign key identified by the names of the columns and object involved.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ForeignKeyName {
+    pub column: String,
+    pub referenced_object: String,
+    pub referenced_column: String,
+}
+
+impl Display for ForeignKeyName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({}) -> {} ({})",
+            self.column, self.referenced_object, self.referenced_column
+        )
+    }
+}
+
+fn kind(obj: &Object) -> &'static str {
+    match obj {
+        Object::Table { .. } => "table",
+        Object::View { .. } => "view",
+        Object::Procedure { .. } => "procedure",
+        Object::Function { .. } => "function",
+    }
+}
+
+/// Gets the columns of an object, keyed by name.
+fn columns<'a>(schema: &'a Schema, obj: &Object) -> BTreeMap<&'a str, &'a Column> {
+    obj.columns()
+        .iter()
+        .filter_map(|id| schema.columns.get(*id))
+        .map(|column| (column.name(), column))
+        .collect()
+}
+
+/// Gets the parameters of a procedure or function, keyed by name.
+fn parameters<'a>(
+    schema: &'a Schema,
+    obj: &Object,
+) -> BTreeMap<&'a str, (&'a Column, ParameterMode)> {
+    obj.parameters()
+        .iter()
+        .filter_map(|p| Some((schema.columns.get(p.column)?, p.mode)))
+        .map(|(column, mode)| (column.name(), (column, mode)))
+        .collect()
+}
+
+/// Gets the foreign keys of an object by name. Foreign keys which reference unknown objects or
+/// columns are ignored.
+fn foreign_keys(schema: &Schema, obj: &Object) -> BTreeSet<ForeignKeyName> {
+    obj.foreign_keys()
+        .iter()
+        .filter_map(|fk| {
+            Some(ForeignKeyName {
+                column: schema.columns.get(fk.column)?.name.clone(),
+                referenced_object: schema.objects.get(fk.referenced_object)?.qualified_name(),
+                referenced_column: schema.columns.get(fk.referenced_column)?.name.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Gets the name identifying an object. Functions may be overloaded, so procedures and functions
+/// are identified by the types of their input parameters as well as their qualified name.
+fn object_name(schema: &Schema, obj: &Object) -> String {
+    match obj {
+        Object::Procedure { parameters, .. } | Object::Function { parameters, .. } => {
+            let types = parameters
+                .iter()
+                .filter(|p| p.mode != ParameterMode::Out)
+                .filter_map(|p| schema.columns.get(p.column))
+                .map(|column| column.data_type.to_string())
+                .collect::<Vec<_>>();
+            format!("{}({})", obj.qualified_name(), types.join(", "))
+        }
+        Object::Table { .. } | Object::View { .. } => obj.qualified_name(),
+    }
+}
+
+/// Gets the objects of a schema, keyed by the name identifying them.
+fn objects(schema: &Schema) -> BTreeMap<String, &Object> {
+    schema
+        .objects
+        .values()
+        .map(|obj| (object_name(schema, obj), obj))
+        .collect()
+}
+
+/// Compares the columns, parameters and foreign keys of an object which exists in both schemas.
+fn diff_object(
+    name: &str,
+    (old_schema, old): (&Schema, &Object),
+    (new_schema, new): (&Schema, &Object),
+    differences: &mut Vec<Difference>,
+) {
+    let old_columns = columns(old_schema, old);
+    let new_columns = columns(new_schema, new);
+    for (column, old_column) in &old_columns {
+        let Some(new_column) = new_columns.get(column) else {
+            differences.push(Difference::RemovedColumn {
+                object: name.to_string(),
+                column: column.to_string(),
+            });
+            continue;
+        };
+
+        if old_column.data_type != new_column.data_type {
+            differences.push(Difference::ChangedType {
+                object: name.to_string(),
+                column: column.to_string(),
+                from: old_column.data_type.clone(),
+                to: new_column.data_type.clone(),
+            });
+        }
+        if old_column.nullable != new_column.nullable {
+            differences.push(Difference::ChangedNullability {
+                object: name.to_string(),
+                column: column.to_string(),
+                nullable: new_column.nullable,
+            });
+        }
+    }
+    for column in new_columns.keys() {
+        if !old_columns.contains_key(column) {
+            differences.push(Difference::AddedColumn {
+                object: name.to_string(),
+                column: column.to_string(),
+            });
+        }
+    }
+
+    let old_parameters = parameters(old_schema, old);
+    let new_parameters = parameters(new_schema, new);
+    for (parameter, (old_column, old_mode)) in &old_parameters {
+        let Some((new_column, new_mode)) = new_parameters.get(parameter) else {
+            differences.push(Difference::RemovedParameter {
+                object: name.to_string(),
+                parameter: parameter.to_string(),
+            });
+            continue;
+        };
+
+        if old_column.data_type != new_column.data_type {
+            differences.push(Difference::ChangedParameterType {
+                object: name.to_string(),
+                parameter: parameter.to_string(),
+                from: old_column.data_type.clone(),
+                to: new_column.data_type.clone(),
+            });
+        }
+        if old_mode != new_mode {
+            differences.push(Difference::ChangedParameterMode {
+                object: name.to_string(),
+                parameter: parameter.to_string(),
+                from: *old_mode,
+                to: *new_mode,
+            });
+        }
+    }
+    for parameter in new_parameters.keys() {
+        if !old_parameters.contains_key(parameter) {
+            differences.push(Difference::AddedParameter {
+                object: name.to_string(),
+                parameter: parameter.to_string(),
+            });
+        }
+    }
+
+    let old_foreign_keys = foreign_keys(old_schema, old);
+    let mut new_foreign_keys = foreign_keys(new_schema, new);
+    for foreign_key in old_foreign_keys {
+        if !new_foreign_keys.remove(&foreign_key) {
+            differences.push(Difference::RemovedForeignKey {
+                object: name.to_string(),
+                foreign_key,
+            });
+        }
+    }
+    for foreign_key in new_foreign_keys {
+        differences.push(Difference::AddedForeignKey {
+            object: name.to_string(),
+            foreign_key,
+        });
+    }
+}
+
+/// Compares two versions of a schema, such as a cached schema and one freshly fetched from the
+/// database, returning the differences ordered by object name.
+pub fn diff(old: &Schema, new: &Schema) -> Vec<Difference> {
+    let old_objects = objects(old);
+    let new_objects = objects(new);
+
+    let names = old_objects
+        .keys()
+        .chain(new_objects.keys())
+        .collect::<BTreeSet<_>>();
+
+    let mut differences = Vec::new();
+    for name in names {
+        match (old_objects.get(name), new_objects.get(name)) {
+            (Some(old_obj), Some(new_obj)) => {
+                if kind(old_obj) != kind(new_obj) {
+                    differences.push(Difference::ChangedKind {
+                        object: name.clone(),
+                        from: kind(old_obj),
+                        to: kind(new_obj),
+                    });
+                }
+                diff_object(name, (old, old_obj), (new, new_obj), &mut differences);
+            }
+            (Some(old_obj), None) => differences.push(Difference::RemovedObject {
+                kind: kind(old_obj),
+                object: name.clone(),
+            }),
+            (None, Some(new_obj)) => differences.push(Difference::AddedObject {
+                kind: kind(new_obj),
+                object: name.clone(),
+            }),
+            (None, None) => {}
+        }
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::builder::{ObjectBuilder, foreign_key};
+
+    fn differences(old: &Schema, new: &Schema) -> Vec<String> {
+        diff(old, new).iter().map(ToString::to_string).collect()
+    }
+
+    /// Builds a schema of users and their orders, with a foreign key from the second column of
+    /// orders to users.
+    fn schema(order_columns: &[(&str, DataType, bool)]) -> Schema {
+        let mut schema = Schema::default();
+        let users = ObjectBuilder::new("users")
+            .namespace("dbo")
+            .column("id", DataType::Integer, false)
+            .table(&mut schema);
+        let orders = order_columns
+            .iter()
+            .fold(
+                ObjectBuilder::new("orders").namespace("dbo"),
+                |builder, (name, data_type, nullable)| {
+                    builder.column(name, data_type.clone(), *nullable)
+                },
+            )
+            .table(&mut schema);
+
+        foreign_key(&mut schema, orders, &[order_columns[1].0], users, &["id"]);
+        schema
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let columns = [
+            ("id", DataType::Integer, false),
+            ("user_id", DataType::Integer, true),
+        ];
+        assert_eq!(diff(&schema(&columns), &schema(&columns)), []);
+    }
+
+    #[test]
+    fn test_diff_columns() {
+        let old = schema(&[
+            ("id", DataType::Integer, false),
+            ("user_id", DataType::Integer, true),
+            ("total", DataType::Integer, true),
+            ("legacy", DataType::Text, true),
+        ]);
+        let mut new = schema(&[
+            ("id", DataType::Integer, false),
+            ("user_id", DataType::Integer, false),
+            ("total", DataType::BigInt, true),
+            ("note", DataType::Text, true),
+        ]);
+        ObjectBuilder::new("audit").namespace("dbo").table(&mut new);
+
+        assert_eq!(
+            differences(&old, &new),
+            [
+                "+ table dbo.audit",
+                "- column dbo.orders.legacy",
+                "~ column dbo.orders.total: int -> bigint",
+                "~ column dbo.orders.user_id: null -> not null",
+                "+ column dbo.orders.note",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_foreign_keys() {
+        let old = schema(&[
+            ("id", DataType::Integer, false),
+            ("user_id", DataType::Integer, true),
+        ]);
+        let new = schema(&[
+            ("id", DataType::Integer, false),
+            ("buyer_id", DataType::Integer, true),
+        ]);
+
+        assert_eq!(
+            differences(&old, &new),
+            [
+                "- column dbo.orders.user_id",
+                "+ column dbo.orders.buyer_id",
+                "- foreign key dbo.orders (user_id) -> dbo.users (id)",
+                "+ foreign key dbo.orders (buyer_id) -> dbo.users (id)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_overloaded_functions() {
+        let mut old = Schema::default();
+        ObjectBuilder::new("total")
+            .namespace("dbo")
+            .parameter("id", DataType::Integer, ParameterMode::In)
+            .function(&mut old);
+        ObjectBuilder::new("total")
+            .namespace("dbo")
+            .parameter("id", DataType::Integer, ParameterMode::In)
+            .parameter("tax", DataType::Double, ParameterMode::In)
+            .function(&mut old);
+
+        // Only one overload has changed, but both are still there.
+        let mut new = Schema::default();
+        ObjectBuilder::new("total")
+            .namespace("dbo")
+            .parameter("order_id", DataType::Integer, ParameterMode::In)
+            .function(&mut new);
+        ObjectBuilder::new("total")
+            .namespace("dbo")
+            .parameter("id", DataType::Integer, ParameterMode::In)
+            .parameter("tax", DataType::Double, ParameterMode::In)
+            .function(&mut new);
+
+        assert_eq!(
+            differences(&old, &new),
+            [
+                "- parameter dbo.total(int) id",
+                "+ parameter dbo.total(int) order_id",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_parameters() {
+        let mut old = Schema::default();
+        ObjectBuilder::new("archive")
+            .namespace("dbo")
+            .parameter("@before", DataType::Date, ParameterMode::In)
+            .parameter("@count", DataType::Integer, ParameterMode::Out)
+            .parameter("@total", DataType::Integer, ParameterMode::Out)
+            .procedure(&mut old);
+
+        let mut new = Schema::default();
+        ObjectBuilder::new("archive")
+            .namespace("dbo")
+            .parameter("@before", DataType::Date, ParameterMode::InOut)
+            .parameter("@count", DataType::BigInt, ParameterMode::Out)
+            .parameter("@rows", DataType::Integer, ParameterMode::Out)
+            .procedure(&mut new);
+
+        // Changing the type of an input parameter identifies a different procedure.
+        ObjectBuilder::new("purge")
+            .namespace("dbo")
+            .parameter("@id", DataType::Integer, ParameterMode::In)
+            .procedure(&mut old);
+        ObjectBuilder::new("purge")
+            .namespace("dbo")
+            .parameter("@id", DataType::BigInt, ParameterMode::In)
+            .procedure(&mut new);
+
+        assert_eq!(
+            differences(&old, &new),
+            [
+                "~ parameter dbo.archive(date) @before: in -> inout",
+                "~ parameter dbo.archive(date) @count: int -> bigint",
+                "- parameter dbo.archive(date) @total",
+                "+ parameter dbo.archive(date) @rows",
+                "+ procedure dbo.purge(bigint)",
+                "- procedure dbo.purge(int)",
+            ]
+        );
+    }
+}
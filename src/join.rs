@@ -0,0 +1,317 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::anyhow;
+
+use crate::{
+    alg::{Name, Scored},
+    schema::{ObjectId, Schema},
+};
+
+/// Maximum number of equal length paths considered when planning a join. Beyond this, the join
+/// must be planned by naming intermediate objects in the query.
+const MAX_CANDIDATE_PATHS: usize = 32;
+
+/// An undirected graph of objects connected by foreign keys.
+pub struct ForeignKeyGraph<'a> {
+    schema: &'a Schema,
+    edges: HashMap<ObjectId, Vec<ObjectId>>,
+}
+
+impl<'a> ForeignKeyGraph<'a> {
+    /// Builds the foreign key graph for a schema.
+    pub fn new(schema: &'a Schema) -> Self {
+        let mut edges: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+        for obj in schema.objects.values() {
+            for fk in obj.foreign_keys() {
+                for (from, to) in [
+                    (obj.id(), fk.referenced_object),
+                    (fk.referenced_object, obj.id()),
+                ] {
+                    let neighbours = edges.entry(from).or_default();
+                    if !neighbours.contains(&to) {
+                        neighbours.push(to);
+                    }
+                }
+            }
+        }
+
+        ForeignKeyGraph { schema, edges }
+    }
+
+    fn neighbours(&self, id: ObjectId) -> &[ObjectId] {
+        self.edges.get(&id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn name(&self, id: ObjectId) -> &str {
+        self.schema.objects.get(id).map(Name::name).unwrap_or("?")
+    }
+
+    /// Finds all of the shortest paths between two objects, ordered by the qualified names of the
+    /// objects along them.
+    ///
+    /// Each path starts with `from` and ends with `to`. If `from` and `to` are the same object,
+    /// the only possible path is through a self-referencing foreign key. An error is returned if
+    /// there are more than [`MAX_CANDIDATE_PATHS`] shortest paths.
+    pub fn shortest_paths(
+        &self,
+        from: ObjectId,
+        to: ObjectId,
+    ) -> anyhow::Result<Vec<Vec<ObjectId>>> {
+        if from == to {
+            return Ok(if self.neighbours(from).contains(&to) {
+                vec![vec![from, to]]
+            } else {
+                Vec::new()
+            });
+        }
+
+        // Breadth first search from `from`, recording every predecessor which lies on a shortest
+        // path to each visited object.
+        let mut distance = HashMap::from([(from, 0usize)]);
+        let mut predecessors: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(id) = queue.pop_front() {
+            let d = distance[&id];
+            if distance.get(&to).is_some_and(|&target| d >= target) {
+                break;
+            }
+
+            for &next in self.neighbours(id) {
+                match distance.get(&next) {
+                    None => {
+                        distance.insert(next, d + 1);
+                        predecessors.entry(next).or_default().push(id);
+                        queue.push_back(next);
+                    }
+                    Some(&n) if n == d + 1 => predecessors.entry(next).or_default().push(id),
+                    Some(_) => {}
+                }
+            }
+        }
+
+        if !distance.contains_key(&to) {
+            return Ok(Vec::new());
+        }
+
+        // Sort the predecessors in reverse, so that they are popped off the stack below in order
+        // of their qualified names.
+        let qualified_name =
+            |id: &ObjectId| self.schema.objects.get(*id).map(|o| o.qualified_name());
+        for ids in predecessors.values_mut() {
+            ids.sort_by_cached_key(|id| std::cmp::Reverse(qualified_name(id)));
+        }
+
+        // Walk the predecessors back from `to` to enumerate the paths.
+        let mut paths = Vec::new();
+        let mut stack = vec![vec![to]];
+        while let Some(partial) = stack.pop() {
+            let head = *partial.last().unwrap();
+            if head == from {
+                paths.push(partial.into_iter().rev().collect());
+                if paths.len() > MAX_CANDIDATE_PATHS {
+                    return Err(anyhow!(
+                        "too many join paths between {} and {}, name an intermediate object to choose one",
+                        self.name(from),
+                        self.name(to)
+                    ));
+                }
+                continue;
+            }
+
+            for &prev in predecessors.get(&head).into_iter().flatten() {
+                let mut next = partial.clone();
+                next.push(prev);
+                stack.push(next);
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Sums the scores of the intermediate objects along a path.
+    fn path_score(&self, path: &[ObjectId]) -> f64 {
+        let intermediates = &path[1..path.len() - 1];
+        intermediates
+            .iter()
+            .filter_map(|id| self.schema.objects.get(*id))
+            .filter_map(|obj| obj.score())
+            .map(|score| score.value)
+            .sum()
+    }
+
+    fn format_path(&self, path: &[ObjectId]) -> String {
+        path.iter()
+            .map(|id| self.name(*id))
+            .collect::<Vec<_>>()
+            .join(">")
+    }
+
+    /// Finds the path used to join `from` to `to`.
+    ///
+    /// The shortest path through the foreign key graph is preferred. If there are multiple
+    /// shortest paths, the one whose intermediate objects have the highest combined score is
+    /// chosen. An error is returned if no path exists, if there are too many shortest paths to
+    /// consider, or if the best paths cannot be told apart.
+    pub fn find_path(&self, from: ObjectId, to: ObjectId) -> anyhow::Result<Vec<ObjectId>> {
+        let mut paths = self
+            .shortest_paths(from, to)?
+            .into_iter()
+            .map(|path| (self.path_score(&path), path))
+            .collect::<Vec<_>>();
+
+        if paths.is_empty() {
+            return Err(anyhow!(
+                "no foreign key path between {} and {}",
+                self.name(from),
+                self.name(to)
+            ));
+        }
+
+        paths.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        let best_score = paths[0].0;
+        let best = paths
+            .iter()
+            .filter(|(score, _)| *score == best_score)
+            .map(|(_, path)| path)
+            .collect::<Vec<_>>();
+
+        if best.len() > 1 {
+            let mut candidates = best
+                .iter()
+                .map(|path| self.format_path(path))
+                .collect::<Vec<_>>();
+            candidates.sort();
+
+            return Err(anyhow!(
+                "ambiguous join path between {} and {}, candidates are: {}",
+                self.name(from),
+                self.name(to),
+                candidates.join(", ")
+            ));
+        }
+
+        let path = paths.swap_remove(0).1;
+        tracing::debug!("Planned join path: {}", self.format_path(&path));
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alg,
+        schema::builder::{ObjectBuilder, foreign_key},
+    };
+
+    /// Adds objects named `names` to `schema`, each linking `a` and `b` by a foreign key to both.
+    fn links(schema: &mut Schema, a: ObjectId, b: ObjectId, names: &[&str]) -> Vec<ObjectId> {
+        names
+            .iter()
+            .map(|name| {
+                let link = ObjectBuilder::new(name)
+                    .columns(&["a_id", "b_id"])
+                    .table(schema);
+                foreign_key(schema, link, &["a_id"], a, &["id"]);
+                foreign_key(schema, link, &["b_id"], b, &["id"]);
+                link
+            })
+            .collect()
+    }
+
+    fn endpoints(schema: &mut Schema) -> (ObjectId, ObjectId) {
+        let a = ObjectBuilder::new("a").columns(&["id"]).table(schema);
+        let b = ObjectBuilder::new("b").columns(&["id"]).table(schema);
+        (a, b)
+    }
+
+    #[test]
+    fn test_multi_hop_path() {
+        let mut schema = Schema::default();
+        let (a, b) = endpoints(&mut schema);
+        let x = ObjectBuilder::new("x")
+            .columns(&["id", "a_id"])
+            .table(&mut schema);
+        let y = ObjectBuilder::new("y")
+            .columns(&["x_id", "b_id"])
+            .table(&mut schema);
+        let z = ObjectBuilder::new("z")
+            .columns(&["a_id"])
+            .table(&mut schema);
+        foreign_key(&mut schema, x, &["a_id"], a, &["id"]);
+        foreign_key(&mut schema, y, &["x_id"], x, &["id"]);
+        foreign_key(&mut schema, y, &["b_id"], b, &["id"]);
+        foreign_key(&mut schema, z, &["a_id"], a, &["id"]);
+
+        let graph = ForeignKeyGraph::new(&schema);
+        assert_eq!(graph.shortest_paths(a, b).unwrap(), [vec![a, x, y, b]]);
+        assert_eq!(graph.find_path(b, a).unwrap(), [b, y, x, a]);
+    }
+
+    #[test]
+    fn test_paths_are_ordered_by_name() {
+        let mut schema = Schema::default();
+        let (a, b) = endpoints(&mut schema);
+        let links = links(&mut schema, a, b, &["y", "z", "x"]);
+
+        let graph = ForeignKeyGraph::new(&schema);
+        assert_eq!(
+            graph.shortest_paths(a, b).unwrap(),
+            [
+                vec![a, links[2], b],
+                vec![a, links[0], b],
+                vec![a, links[1], b],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tie_broken_by_score() {
+        let mut schema = Schema::default();
+        let (a, b) = endpoints(&mut schema);
+        let links = links(&mut schema, a, b, &["x", "y"]);
+        alg::update_score(schema.objects[links[0]].score_mut());
+        alg::update_score(schema.objects[links[1]].score_mut());
+        alg::update_score(schema.objects[links[1]].score_mut());
+
+        let graph = ForeignKeyGraph::new(&schema);
+        assert_eq!(graph.find_path(a, b).unwrap(), [a, links[1], b]);
+    }
+
+    #[test]
+    fn test_ambiguous_path() {
+        let mut schema = Schema::default();
+        let (a, b) = endpoints(&mut schema);
+        let links = links(&mut schema, a, b, &["x", "y", "z"]);
+        alg::update_score(schema.objects[links[0]].score_mut());
+        alg::update_score(schema.objects[links[2]].score_mut());
+
+        let graph = ForeignKeyGraph::new(&schema);
+        assert_eq!(
+            graph.find_path(a, b).unwrap_err().to_string(),
+            "ambiguous join path between a and b, candidates are: a>x>b, a>z>b"
+        );
+    }
+
+    #[test]
+    fn test_too_many_paths() {
+        let mut schema = Schema::default();
+        let (a, b) = endpoints(&mut schema);
+        let names = (0..=MAX_CANDIDATE_PATHS)
+            .map(|i| format!("link{}", i))
+            .collect::<Vec<_>>();
+        let links = links(
+            &mut schema,
+            a,
+            b,
+            &names.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        alg::update_score(schema.objects[links[0]].score_mut());
+
+        let graph = ForeignKeyGraph::new(&schema);
+        assert_eq!(
+            graph.find_path(a, b).unwrap_err().to_string(),
+            "too many join paths between a and b, name an intermediate object to choose one"
+        );
+    }
+}
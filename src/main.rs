@@ -1,25 +1,44 @@
-use std::{env, fs::File, process::ExitCode};
+use std::{
+    env,
+    io::{self, BufWriter},
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::anyhow;
-use clap::{Parser, Subcommand, command};
+use clap::{Parser, Subcommand};
 use tracing_subscriber::fmt::format::FmtSpan;
 
 use crate::{
-    alg::{Name, Scored},
+    alg::Scored,
     config::{Config, Profile},
     db::Database,
-    schema::Schema,
+    output::Format,
+    schema::{ColumnId, Identity, Object, ParameterMode, Schema},
 };
 
 mod alg;
+mod cache;
 mod config;
 mod db;
+mod diff;
+mod join;
+mod output;
 mod query;
 mod schema;
 mod sql;
+mod translate;
 
 #[derive(Debug, Parser)]
 struct QueryOpts {
+    /// Maximum number of rows to return.
+    #[arg(short = 'n', long)]
+    limit: Option<u64>,
+
+    /// Number of rows to skip before returning any.
+    #[arg(long)]
+    offset: Option<u64>,
+
     /// The query to execute.
     #[arg(trailing_var_arg = true)]
     query: Vec<String>,
@@ -29,6 +48,17 @@ struct QueryOpts {
 struct DefineOpts {
     /// Name of the object to define.
     object: Option<String>,
+
+    /// Also show the indexes on the object.
+    #[arg(long)]
+    indexes: bool,
+}
+
+#[derive(Debug, Parser)]
+struct DiffOpts {
+    /// Replace the cached schema with the one fetched from the database.
+    #[arg(long)]
+    update: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -40,6 +70,9 @@ enum Command {
     /// Shows the definition of an object.
     #[command(alias = "d")]
     Define(DefineOpts),
+
+    /// Shows how the database has changed since its schema was cached.
+    Diff(DiffOpts),
 }
 
 /// sq - Simple Query
@@ -59,6 +92,10 @@ struct Opts {
     /// Do not use cached schema information.
     #[arg(long, global = true)]
     no_cache: bool,
+
+    /// Format in which query results are written.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    format: Format,
 }
 
 /// Loads the cached schema for a given profile.
@@ -66,23 +103,22 @@ struct Opts {
 /// Returns [`None`] if no cached schema exits for the given profile or the schema could not be
 /// read. In either case, the schema should be fetched from the database instead.
 fn load_cached_schema(profile: &Profile) -> Option<Schema> {
-    let path = profile.schema_path()?;
-    let file = File::open(&path).ok()?;
-    let schema = serde_json::from_reader(file).ok()?;
-    tracing::info!("Loaded schema from {}", path.to_string_lossy());
-    Some(schema)
+    cache::load(&profile.schema_path()?)
 }
 
-fn save_schema(profile: &Profile, schema: &Schema) -> anyhow::Result<()> {
+/// Saves the schema for a given profile to the cache, applying `update` to it once any scores
+/// saved by other processes since it was loaded have been merged in. See [`cache::save`].
+fn save_schema(
+    profile: &Profile,
+    schema: &mut Schema,
+    update: impl FnOnce(&mut Schema),
+) -> anyhow::Result<()> {
     let Some(path) = profile.schema_path() else {
         tracing::warn!("Unable to determine schema path, not saving");
         return Ok(());
     };
 
-    let file = File::create(&path)?;
-    serde_json::to_writer(file, schema)?;
-    tracing::info!("Saved schema to {}", path.to_string_lossy());
-    Ok(())
+    cache::save(&path, schema, update)
 }
 
 fn connect(config: &Config, opts: &Opts) -> anyhow::Result<Box<dyn Database>> {
@@ -100,36 +136,187 @@ fn connect(config: &Config, opts: &Opts) -> anyhow::Result<Box<dyn Database>> {
     })
 }
 
-#[tracing::instrument(skip_all, err)]
-fn query(_config: &Config, _opts: &Opts, query_opts: &QueryOpts) -> anyhow::Result<()> {
-    let query = query_opts.query.join(" ");
-    let _ = query::parse(&query)?;
-    todo!()
-}
-
-#[tracing::instrument(skip_all, err)]
-fn define(config: &Config, opts: &Opts, define_opts: &DefineOpts) -> anyhow::Result<()> {
+fn profile<'a>(config: &'a Config, opts: &Opts) -> anyhow::Result<&'a Profile> {
     let profile = opts.profile.as_deref().unwrap_or("default");
     let Some(profile) = config.profiles.get(profile) else {
         tracing::error!("Profile not found: {}", profile);
         return Err(anyhow!("unknown profile"));
     };
 
-    let mut schema = if !opts.no_cache
-        && let Some(schema) = load_cached_schema(profile)
-    {
-        schema
-    } else {
-        let database = connect(config, opts)?;
-        database.schema()?
+    Ok(profile)
+}
+
+/// Gets the current time in seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Whether a cached schema is older than the profile's cache TTL.
+///
+/// Schemas cached before fetch times were recorded are always considered expired.
+fn is_expired(profile: &Profile, schema: &Schema) -> bool {
+    schema.fetched_at == 0
+        || profile
+            .cache_ttl
+            .is_some_and(|ttl| now().saturating_sub(schema.fetched_at) >= ttl)
+}
+
+/// Fetches the schema from a database, recording when it was fetched and the database's
+/// fingerprint at the time.
+fn fetch_schema(database: &dyn Database) -> anyhow::Result<Schema> {
+    // The fingerprint is taken first, so that changes made while the schema is being fetched
+    // are detected the next time it is loaded.
+    let fingerprint = database.fingerprint()?;
+    let mut schema = database.schema()?;
+    schema.fetched_at = now();
+    schema.fingerprint = fingerprint;
+    Ok(schema)
+}
+
+/// Loads the schema for a profile, either from the cache or from the database if there is no
+/// cached schema, caching is disabled or the cached schema is out of date. Scores from the cached
+/// schema are kept when the schema is fetched from the database.
+///
+/// A cached schema is out of date once the profile's cache TTL has elapsed, or when the
+/// database's fingerprint no longer matches the one it was cached with. Checking the fingerprint
+/// requires connecting to the database, so this is skipped for databases without one.
+///
+/// If the database can't be reached or read, the cached schema is used even when it is out of
+/// date, unless caching is disabled.
+///
+/// An already open connection may be passed to avoid connecting to the database twice.
+fn load_schema(
+    config: &Config,
+    opts: &Opts,
+    profile: &Profile,
+    database: Option<&dyn Database>,
+) -> anyhow::Result<Schema> {
+    let is_usable = |schema: &Schema| !opts.no_cache && !is_expired(profile, schema);
+    let cached = match load_cached_schema(profile) {
+        Some(schema) if is_usable(&schema) && schema.fingerprint.is_none() => return Ok(schema),
+        cached => cached,
+    };
+
+    let fallback = |cached: Option<Schema>, err: anyhow::Error| match cached {
+        Some(schema) if !opts.no_cache => {
+            tracing::warn!("Using cached schema, which may be out of date: {:#}", err);
+            Ok(schema)
+        }
+        _ => Err(err),
+    };
+
+    let connection;
+    let database = match database {
+        Some(database) => database,
+        None => match connect(config, opts) {
+            Ok(database) => {
+                connection = database;
+                connection.as_ref()
+            }
+            Err(err) => return fallback(cached, err),
+        },
+    };
+
+    let cached = match cached {
+        Some(schema) if is_usable(&schema) => match database.fingerprint() {
+            Ok(fingerprint) if fingerprint == schema.fingerprint => return Ok(schema),
+            Ok(_) => Some(schema),
+            Err(err) => return fallback(Some(schema), err),
+        },
+        cached => cached,
+    };
+
+    tracing::info!("Fetching schema from the database");
+    let mut schema = match fetch_schema(database) {
+        Ok(schema) => schema,
+        Err(err) => return fallback(cached, err),
+    };
+
+    // Keep the scores from the cached schema, so that refreshing it does not reset the ranking
+    // of objects and columns.
+    if let Some(cached) = cached {
+        schema.merge_scores(&cached);
+    }
+    Ok(schema)
+}
+
+#[tracing::instrument(skip_all, err)]
+fn query(config: &Config, opts: &Opts, query_opts: &QueryOpts) -> anyhow::Result<()> {
+    let profile = profile(config, opts)?;
+    let query = query_opts.query.join(" ");
+    let query = query::parse(&query)?;
+
+    let database = connect(config, opts)?;
+    let mut schema = load_schema(config, opts, profile, Some(database.as_ref()))?;
+
+    let resolved = translate::resolve(query, &mut schema)?;
+    let mut query = translate::lower(&resolved, &schema)?;
+    query.limit = query_opts.limit;
+    query.offset = query_opts.offset;
+
+    let statement = database.dialect().query(&query);
+    tracing::debug!("Generated SQL: {}", statement.sql);
+
+    let mut writer = output::writer(opts.format, BufWriter::new(io::stdout().lock()));
+    database.execute(&statement.sql, &statement.params, writer.as_mut())?;
+    writer.finish()?;
+
+    save_schema(profile, &mut schema, |schema| {
+        translate::update_scores(&resolved, schema)
+    })?;
+    Ok(())
+}
+
+/// Formats the signature of a procedure or function, such as
+/// `dbo.orders_for(@customer int) returns table`.
+///
+/// Returns [`None`] for other objects.
+fn signature(schema: &Schema, obj: &Object) -> Option<String> {
+    let return_type = match obj {
+        Object::Procedure { .. } => None,
+        Object::Function {
+            return_type: Some(return_type),
+            ..
+        } => Some(return_type.to_string()),
+        Object::Function { .. } => Some("table".to_string()),
+        Object::Table { .. } | Object::View { .. } => return None,
     };
 
+    let parameters = obj
+        .parameters()
+        .iter()
+        .filter_map(|parameter| {
+            let column = schema.columns.get(parameter.column)?;
+            Some(match parameter.mode {
+                ParameterMode::In => format!("{} {}", column.name, column.data_type),
+                mode => format!("{} {} {}", column.name, column.data_type, mode),
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut signature = format!("{}({})", obj.qualified_name(), parameters);
+    if let Some(return_type) = return_type {
+        signature.push_str(" returns ");
+        signature.push_str(&return_type);
+    }
+    Some(signature)
+}
+
+#[tracing::instrument(skip_all, err)]
+fn define(config: &Config, opts: &Opts, define_opts: &DefineOpts) -> anyhow::Result<()> {
+    let profile = profile(config, opts)?;
+    let mut schema = load_schema(config, opts, profile, None)?;
+
     let Some(object_name) = &define_opts.object else {
         for obj in schema.objects.values() {
-            println!("{}", obj.name());
+            println!("{}", obj.qualified_name());
         }
 
-        save_schema(profile, &schema)?;
+        save_schema(profile, &mut schema, |_| {})?;
         return Ok(());
     };
 
@@ -137,11 +324,22 @@ fn define(config: &Config, opts: &Opts, define_opts: &DefineOpts) -> anyhow::Res
         tracing::error!("Object not found: {}", object_name);
         return Err(anyhow!("unknown object"));
     };
-    alg::update_score(obj.score_mut());
+    let id = obj.id();
+    let obj = &schema.objects[id];
 
-    let column_ids = match obj {
-        schema::Object::Table { columns, .. } | schema::Object::View { columns, .. } => columns,
-    };
+    if let Some(description) = obj.description() {
+        println!("{}", description);
+        println!();
+    }
+
+    if let Some(signature) = signature(&schema, obj) {
+        println!("{}", signature);
+        if !obj.columns().is_empty() {
+            println!();
+        }
+    }
+
+    let column_ids = obj.columns();
 
     let mut columns = Vec::with_capacity(column_ids.len());
     for column_id in column_ids {
@@ -153,11 +351,106 @@ fn define(config: &Config, opts: &Opts, define_opts: &DefineOpts) -> anyhow::Res
     }
 
     columns.sort_by(|a, b| a.name.cmp(&b.name));
+    let width = columns.iter().map(|c| c.name.len()).max().unwrap_or(0);
     for column in columns {
-        println!("{}", column.name);
+        let mut attributes = String::new();
+        if !column.nullable {
+            attributes.push_str(" not null");
+        }
+        if let Some(default) = &column.default {
+            attributes.push_str(&format!(" default {}", default));
+        }
+        match &column.identity {
+            Some(Identity {
+                seed: Some(seed),
+                increment: Some(increment),
+            }) => attributes.push_str(&format!(" identity({}, {})", seed, increment)),
+            Some(_) => attributes.push_str(" identity"),
+            None => {}
+        }
+        if let Some(computed) = &column.computed {
+            attributes.push_str(&format!(" as {}", computed));
+        }
+        // Descriptions are kept to the column's line, so that each column is listed on one line.
+        if let Some(description) = &column.description {
+            let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+            attributes.push_str(&format!("  -- {}", description));
+        }
+
+        println!(
+            "{:width$}  {}{}",
+            column.name,
+            column.data_type,
+            attributes,
+            width = width
+        );
+    }
+
+    let column_names = |ids: &[ColumnId]| {
+        ids.iter()
+            .filter_map(|id| schema.columns.get(*id))
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    if obj.primary_key().is_some() || !obj.unique_keys().is_empty() {
+        println!();
+    }
+    if let Some(key) = obj.primary_key() {
+        println!("primary key ({})", column_names(&key.columns));
+    }
+    for key in obj.unique_keys() {
+        match &key.name {
+            Some(name) => println!("unique {} ({})", name, column_names(&key.columns)),
+            None => println!("unique ({})", column_names(&key.columns)),
+        }
     }
 
-    save_schema(profile, &schema)?;
+    if define_opts.indexes && !obj.indexes().is_empty() {
+        println!();
+        for index in obj.indexes() {
+            let unique = if index.unique { "unique " } else { "" };
+            let clustered = if index.clustered { "clustered " } else { "" };
+            print!(
+                "{}{}index {} ({})",
+                unique,
+                clustered,
+                index.name,
+                column_names(&index.columns)
+            );
+            if !index.included_columns.is_empty() {
+                print!(" include ({})", column_names(&index.included_columns));
+            }
+            if let Some(filter) = &index.filter {
+                print!(" where {}", filter);
+            }
+            println!();
+        }
+    }
+
+    save_schema(profile, &mut schema, |schema| {
+        alg::update_score(schema.objects[id].score_mut())
+    })?;
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, err)]
+fn diff(config: &Config, opts: &Opts, diff_opts: &DiffOpts) -> anyhow::Result<()> {
+    let profile = profile(config, opts)?;
+    let Some(cached) = load_cached_schema(profile) else {
+        return Err(anyhow!("no cached schema"));
+    };
+
+    let mut live = fetch_schema(connect(config, opts)?.as_ref())?;
+    for difference in diff::diff(&cached, &live) {
+        println!("{}", difference);
+    }
+
+    // Saving keeps the scores from the cache, so they don't need to be merged here.
+    if diff_opts.update {
+        save_schema(profile, &mut live, |_| {})?;
+    }
     Ok(())
 }
 
@@ -167,6 +460,7 @@ fn run(opts: Opts) -> anyhow::Result<()> {
     match &opts.command {
         Command::Query(query_opts) => query(&cfg, &opts, query_opts)?,
         Command::Define(define_opts) => define(&cfg, &opts, define_opts)?,
+        Command::Diff(diff_opts) => diff(&cfg, &opts, diff_opts)?,
     }
 
     Ok(())
@@ -0,0 +1,93 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+
+use crate::db::{ColumnInfo, RowSink};
+
+mod delimited;
+mod json;
+mod markdown;
+mod table;
+
+pub use delimited::{CsvWriter, TsvWriter};
+pub use json::{JsonWriter, NdjsonWriter};
+pub use markdown::MarkdownWriter;
+pub use table::TableWriter;
+
+/// Formats in which query results can be written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Aligned text table sized to fit the terminal.
+    #[default]
+    Table,
+
+    /// Comma separated values.
+    Csv,
+
+    /// Tab separated values.
+    Tsv,
+
+    /// A JSON array of objects.
+    Json,
+
+    /// Newline delimited JSON objects.
+    Ndjson,
+
+    /// A Markdown table.
+    Markdown,
+}
+
+/// Writes the result of a query to an output stream.
+pub(crate) trait ResultWriter: RowSink {
+    /// Writes any remaining output once all rows have been received and flushes the underlying
+    /// stream.
+    fn finish(&mut self) -> anyhow::Result<()>;
+}
+
+/// Creates a writer for query results in a given format.
+pub(crate) fn writer<'a, W: Write + 'a>(format: Format, writer: W) -> Box<dyn ResultWriter + 'a> {
+    match format {
+        Format::Table => Box::new(TableWriter::for_terminal(writer)),
+        Format::Csv => Box::new(CsvWriter::new(writer)),
+        Format::Tsv => Box::new(TsvWriter::new(writer)),
+        Format::Json => Box::new(JsonWriter::new(writer)),
+        Format::Ndjson => Box::new(NdjsonWriter::new(writer)),
+        Format::Markdown => Box::new(MarkdownWriter::new(writer)),
+    }
+}
+
+/// Gets the names of the given columns, suffixing duplicates so that every name is unique.
+///
+/// Queries over joined objects often contain several columns with the same name (e.g., `id`),
+/// which would otherwise collide in keyed formats such as JSON.
+fn unique_names(columns: &[ColumnInfo]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(columns.len());
+    for column in columns {
+        let mut name = column.name.clone();
+        let mut n = 1;
+        while names.contains(&name) {
+            n += 1;
+            name = format!("{}_{}", column.name, n);
+        }
+
+        names.push(name);
+    }
+
+    names
+}
+
+#[cfg(test)]
+fn render(writer: &mut dyn ResultWriter, columns: &[&str], rows: &[&[Option<&str>]]) {
+    let columns = columns
+        .iter()
+        .map(|name| ColumnInfo {
+            name: name.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    writer.columns(&columns).unwrap();
+    for row in rows {
+        writer.row(row).unwrap();
+    }
+    writer.finish().unwrap();
+}
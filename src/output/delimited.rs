@@ -0,0 +1,154 @@
+use std::io::Write;
+
+use crate::{
+    db::{ColumnInfo, RowSink},
+    output::ResultWriter,
+};
+
+/// Writes query results as comma separated values following RFC 4180.
+///
+/// Fields containing commas, quotes or line breaks are quoted. `NULL` values are written as empty
+/// fields, while empty strings are written as `""` so that the two can be told apart, as done by
+/// `COPY ... CSV` in PostgreSQL.
+pub struct CsvWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(writer: W) -> Self {
+        CsvWriter { writer }
+    }
+
+    fn write_record<'a>(
+        &mut self,
+        values: impl Iterator<Item = Option<&'a str>>,
+    ) -> anyhow::Result<()> {
+        let fields = values.map(|value| match value {
+            None => String::new(),
+            Some(value) if value.is_empty() || value.contains([',', '"', '\n', '\r']) => {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            }
+            Some(value) => value.to_string(),
+        });
+
+        let line = fields.collect::<Vec<_>>().join(",");
+        write!(self.writer, "{}\r\n", line)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RowSink for CsvWriter<W> {
+    fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+        self.write_record(columns.iter().map(|c| Some(c.name.as_str())))
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        self.write_record(row.iter().copied())
+    }
+}
+
+impl<W: Write> ResultWriter for CsvWriter<W> {
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes query results as tab separated values.
+///
+/// Backslashes, tabs and line breaks within values are escaped (`\\`, `\t`, `\n` and `\r`) and
+/// `NULL` values are written as `\N`, matching the text format used by `COPY` in PostgreSQL and
+/// `LOAD DATA` in MySQL.
+pub struct TsvWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> TsvWriter<W> {
+    pub fn new(writer: W) -> Self {
+        TsvWriter { writer }
+    }
+
+    fn write_record<'a>(
+        &mut self,
+        values: impl Iterator<Item = Option<&'a str>>,
+    ) -> anyhow::Result<()> {
+        let fields = values.map(|value| match value {
+            None => String::from("\\N"),
+            Some(value) => {
+                let mut escaped = String::with_capacity(value.len());
+                for c in value.chars() {
+                    match c {
+                        '\\' => escaped.push_str("\\\\"),
+                        '\t' => escaped.push_str("\\t"),
+                        '\n' => escaped.push_str("\\n"),
+                        '\r' => escaped.push_str("\\r"),
+                        c => escaped.push(c),
+                    }
+                }
+                escaped
+            }
+        });
+
+        let line = fields.collect::<Vec<_>>().join("\t");
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RowSink for TsvWriter<W> {
+    fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+        self.write_record(columns.iter().map(|c| Some(c.name.as_str())))
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        self.write_record(row.iter().copied())
+    }
+}
+
+impl<W: Write> ResultWriter for TsvWriter<W> {
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render;
+
+    #[test]
+    fn test_csv() {
+        let mut buf = Vec::new();
+        render(
+            &mut CsvWriter::new(&mut buf),
+            &["id", "note"],
+            &[
+                &[Some("1"), Some("plain")],
+                &[Some("2"), Some("a, \"quoted\"\nvalue")],
+                &[Some("3"), None],
+                &[Some("4"), Some("")],
+            ],
+        );
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "id,note\r\n1,plain\r\n2,\"a, \"\"quoted\"\"\nvalue\"\r\n3,\r\n4,\"\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_tsv() {
+        let mut buf = Vec::new();
+        render(
+            &mut TsvWriter::new(&mut buf),
+            &["id", "note"],
+            &[&[Some("1"), Some("a\tb\\c\nd")], &[Some("2"), None]],
+        );
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "id\tnote\n1\ta\\tb\\\\c\\nd\n2\t\\N\n"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::io::Write;
+
+use crate::{
+    db::{ColumnInfo, RowSink},
+    output::{ResultWriter, unique_names},
+};
+
+/// Formats a row as a JSON object keyed by column name.
+///
+/// The object is built by hand rather than through [`serde_json::Map`] so that keys stay in
+/// column order.
+fn object(names: &[String], row: &[Option<&str>]) -> anyhow::Result<String> {
+    let mut object = String::from("{");
+    for (i, (name, value)) in names.iter().zip(row).enumerate() {
+        if i > 0 {
+            object.push(',');
+        }
+
+        object.push_str(&serde_json::to_string(name)?);
+        object.push(':');
+        object.push_str(&serde_json::to_string(value)?);
+    }
+
+    object.push('}');
+    Ok(object)
+}
+
+/// Writes query results as a JSON array of objects, one per row.
+///
+/// Values are written as JSON strings, or `null` for `NULL` values.
+pub struct JsonWriter<W> {
+    writer: W,
+    names: Vec<String>,
+    rows: usize,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonWriter {
+            writer,
+            names: Vec::new(),
+            rows: 0,
+        }
+    }
+}
+
+impl<W: Write> RowSink for JsonWriter<W> {
+    fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+        self.names = unique_names(columns);
+        Ok(())
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        let separator = if self.rows == 0 { "[" } else { "," };
+        writeln!(self.writer, "{}", separator)?;
+        write!(self.writer, "  {}", object(&self.names, row)?)?;
+        self.rows += 1;
+        Ok(())
+    }
+}
+
+impl<W: Write> ResultWriter for JsonWriter<W> {
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if self.rows == 0 {
+            writeln!(self.writer, "[]")?;
+        } else {
+            writeln!(self.writer, "\n]")?;
+        }
+
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes query results as newline delimited JSON, with one object per row.
+pub struct NdjsonWriter<W> {
+    writer: W,
+    names: Vec<String>,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        NdjsonWriter {
+            writer,
+            names: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> RowSink for NdjsonWriter<W> {
+    fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+        self.names = unique_names(columns);
+        Ok(())
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        writeln!(self.writer, "{}", object(&self.names, row)?)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> ResultWriter for NdjsonWriter<W> {
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render;
+
+    #[test]
+    fn test_json() {
+        let mut buf = Vec::new();
+        render(
+            &mut JsonWriter::new(&mut buf),
+            &["id", "name", "id"],
+            &[
+                &[Some("1"), Some("a \"b\""), Some("10")],
+                &[Some("2"), None, None],
+            ],
+        );
+
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            output,
+            "[\n  {\"id\":\"1\",\"name\":\"a \\\"b\\\"\",\"id_2\":\"10\"},\n  \
+             {\"id\":\"2\",\"name\":null,\"id_2\":null}\n]\n"
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&output).is_ok());
+    }
+
+    #[test]
+    fn test_json_without_rows() {
+        let mut buf = Vec::new();
+        render(&mut JsonWriter::new(&mut buf), &["id"], &[]);
+        assert_eq!(String::from_utf8(buf).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let mut buf = Vec::new();
+        render(
+            &mut NdjsonWriter::new(&mut buf),
+            &["id", "name"],
+            &[&[Some("1"), Some("alice")], &[Some("2"), None]],
+        );
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\"id\":\"1\",\"name\":\"alice\"}\n{\"id\":\"2\",\"name\":null}\n"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::io::Write;
+
+use crate::{
+    db::{ColumnInfo, RowSink},
+    output::ResultWriter,
+};
+
+/// Writes query results as a Markdown (GitHub flavoured) table.
+///
+/// Pipes within values are escaped and line breaks are replaced with `<br>` so that each row stays
+/// on a single line. `NULL` values are written as an italicised `NULL`.
+pub struct MarkdownWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> MarkdownWriter<W> {
+    pub fn new(writer: W) -> Self {
+        MarkdownWriter { writer }
+    }
+
+    fn write_record<'a>(
+        &mut self,
+        values: impl Iterator<Item = Option<&'a str>>,
+    ) -> anyhow::Result<()> {
+        let cells = values.map(|value| match value {
+            None => String::from("*NULL*"),
+            Some(value) => value
+                .replace('\\', "\\\\")
+                .replace('|', "\\|")
+                .replace("\r\n", "<br>")
+                .replace(['\n', '\r'], "<br>"),
+        });
+
+        writeln!(self.writer, "| {} |", cells.collect::<Vec<_>>().join(" | "))?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RowSink for MarkdownWriter<W> {
+    fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+        self.write_record(columns.iter().map(|c| Some(c.name.as_str())))?;
+        let rule = vec!["---"; columns.len()].join(" | ");
+        writeln!(self.writer, "| {} |", rule)?;
+        Ok(())
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        self.write_record(row.iter().copied())
+    }
+}
+
+impl<W: Write> ResultWriter for MarkdownWriter<W> {
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render;
+
+    #[test]
+    fn test_markdown() {
+        let mut buf = Vec::new();
+        render(
+            &mut MarkdownWriter::new(&mut buf),
+            &["id", "note"],
+            &[&[Some("1"), Some("a|b\nc")], &[Some("2"), None]],
+        );
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "| id | note |\n| --- | --- |\n| 1 | a\\|b<br>c |\n| 2 | *NULL* |\n"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use std::io::Write;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    db::{ColumnInfo, RowSink},
+    output::ResultWriter,
+};
+
+/// Number of rows buffered to determine column widths before any output is written.
+const SAMPLE_ROWS: usize = 200;
+
+/// Maximum width of a column, unless it is wide enough to fit a column header.
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// Minimum width of a column when shrinking columns to fit the terminal.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+/// Separator placed between columns.
+const SEPARATOR: &str = " | ";
+
+/// Text shown in place of `NULL` values.
+const NULL: &str = "NULL";
+
+/// Writes query results as an aligned text table.
+///
+/// Column widths are determined from the header and the first [`SAMPLE_ROWS`] rows, after which
+/// rows are written as they are received. Values which do not fit within their column are
+/// truncated and marked with an ellipsis.
+pub struct TableWriter<W> {
+    writer: W,
+
+    /// Maximum width of a line, typically the width of the terminal.
+    max_width: Option<usize>,
+
+    /// Whether `NULL` values should be styled to distinguish them from text.
+    style_nulls: bool,
+
+    columns: Vec<String>,
+    widths: Option<Vec<usize>>,
+    buffered: Vec<Vec<Option<String>>>,
+}
+
+impl<W: Write> TableWriter<W> {
+    pub fn new(writer: W, max_width: Option<usize>, style_nulls: bool) -> Self {
+        TableWriter {
+            writer,
+            max_width,
+            style_nulls,
+            columns: Vec::new(),
+            widths: None,
+            buffered: Vec::new(),
+        }
+    }
+
+    /// Creates a table writer which fits its output to the terminal attached to stdout, if any.
+    pub fn for_terminal(writer: W) -> Self {
+        let max_width = terminal_size::terminal_size().map(|(width, _)| width.0 as usize);
+        Self::new(writer, max_width, max_width.is_some())
+    }
+
+    /// Computes column widths from the buffered rows and writes the header and buffered rows.
+    fn write_buffered(&mut self) -> anyhow::Result<()> {
+        let mut widths = self
+            .columns
+            .iter()
+            .map(|name| display_width(name))
+            .collect::<Vec<_>>();
+
+        for row in &self.buffered {
+            for (width, value) in widths.iter_mut().zip(row) {
+                let value_width = value.as_deref().map(display_width).unwrap_or(NULL.len());
+                *width = (*width).max(value_width.min(MAX_COLUMN_WIDTH));
+            }
+        }
+
+        if let Some(max_width) = self.max_width {
+            fit_widths(&mut widths, max_width);
+        }
+
+        let header = self.columns.iter().map(|name| Some(name.as_str()));
+        let line = self.format_line(&widths, header, false);
+        writeln!(self.writer, "{}", line)?;
+
+        let rule = widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-+-");
+        writeln!(self.writer, "{}", rule)?;
+
+        for row in std::mem::take(&mut self.buffered) {
+            let line = self.format_line(&widths, row.iter().map(Option::as_deref), true);
+            writeln!(self.writer, "{}", line)?;
+        }
+
+        self.widths = Some(widths);
+        Ok(())
+    }
+
+    fn format_line<'a>(
+        &self,
+        widths: &[usize],
+        values: impl Iterator<Item = Option<&'a str>>,
+        nulls: bool,
+    ) -> String {
+        let mut line = String::new();
+        for (i, (width, value)) in widths.iter().zip(values).enumerate() {
+            if i > 0 {
+                line.push_str(SEPARATOR);
+            }
+
+            let (text, len) = match value {
+                Some(value) => {
+                    let text = truncate(value, *width);
+                    let len = display_width(&text);
+                    (text, len)
+                }
+                None if nulls && self.style_nulls => (format!("\x1b[2m{}\x1b[0m", NULL), 4),
+                None => (truncate(NULL, *width), NULL.len().min(*width)),
+            };
+
+            line.push_str(&text);
+            line.extend(std::iter::repeat_n(' ', width.saturating_sub(len)));
+        }
+
+        line.trim_end().to_string()
+    }
+}
+
+impl<W: Write> RowSink for TableWriter<W> {
+    fn columns(&mut self, columns: &[ColumnInfo]) -> anyhow::Result<()> {
+        self.columns = columns.iter().map(|c| c.name.clone()).collect();
+        Ok(())
+    }
+
+    fn row(&mut self, row: &[Option<&str>]) -> anyhow::Result<()> {
+        match &self.widths {
+            Some(widths) => {
+                let line = self.format_line(widths, row.iter().copied(), true);
+                writeln!(self.writer, "{}", line)?;
+            }
+
+            None => {
+                self.buffered
+                    .push(row.iter().map(|v| v.map(String::from)).collect());
+                if self.buffered.len() >= SAMPLE_ROWS {
+                    self.write_buffered()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> ResultWriter for TableWriter<W> {
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if self.widths.is_none() {
+            self.write_buffered()?;
+        }
+
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Gets the width of a character in terminal columns. Control characters are replaced by spaces
+/// when written, so they occupy a single column.
+fn char_width(c: char) -> usize {
+    if c.is_control() {
+        1
+    } else {
+        c.width().unwrap_or(0)
+    }
+}
+
+/// Gets the width of a string in terminal columns, accounting for wide characters such as CJK
+/// ideographs and for zero width combining characters.
+fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+/// Truncates a value to fit within a given width, replacing control characters so that they do
+/// not break the layout of the table.
+fn truncate(value: &str, width: usize) -> String {
+    let clean = |c: char| if c.is_control() { ' ' } else { c };
+    if display_width(value) <= width {
+        return value.chars().map(clean).collect();
+    }
+
+    // Leave room for the ellipsis, which is a single column wide.
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in value.chars() {
+        used += char_width(c);
+        if used > width.saturating_sub(1) {
+            break;
+        }
+        truncated.push(clean(c));
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Shrinks the widest columns until the table fits within `max_width`, without shrinking any
+/// column below [`MIN_COLUMN_WIDTH`].
+fn fit_widths(widths: &mut [usize], max_width: usize) {
+    let separators = SEPARATOR.len() * widths.len().saturating_sub(1);
+    loop {
+        let total = widths.iter().sum::<usize>() + separators;
+        if total <= max_width {
+            return;
+        }
+
+        let Some(widest) = widths
+            .iter_mut()
+            .filter(|w| **w > MIN_COLUMN_WIDTH)
+            .max_by_key(|w| **w)
+        else {
+            return;
+        };
+        *widest -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(max_width: Option<usize>, columns: &[&str], rows: &[&[Option<&str>]]) -> String {
+        let mut buf = Vec::new();
+        crate::output::render(
+            &mut TableWriter::new(&mut buf, max_width, false),
+            columns,
+            rows,
+        );
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_aligned_table() {
+        let output = render(
+            None,
+            &["id", "name"],
+            &[&[Some("1"), Some("alice")], &[Some("42"), Some("bob")]],
+        );
+
+        assert_eq!(output, "id | name\n---+------\n1  | alice\n42 | bob\n");
+    }
+
+    #[test]
+    fn test_null_differs_from_empty_string() {
+        let output = render(None, &["a", "b"], &[&[None, Some("")]]);
+        assert_eq!(output, "a    | b\n-----+--\nNULL |\n");
+    }
+
+    #[test]
+    fn test_long_values_are_truncated() {
+        let long = "x".repeat(MAX_COLUMN_WIDTH + 10);
+        let output = render(None, &["v"], &[&[Some(long.as_str())]]);
+        let expected = format!("{}…", "x".repeat(MAX_COLUMN_WIDTH - 1));
+        assert_eq!(output.lines().nth(2), Some(expected.as_str()));
+    }
+
+    #[test]
+    fn test_fits_terminal_width() {
+        let output = render(
+            Some(16),
+            &["id", "description"],
+            &[&[Some("1"), Some("a rather long description")]],
+        );
+
+        assert!(output.lines().all(|line| display_width(line) <= 16));
+        assert_eq!(output.lines().nth(2), Some("1  | a rather l…"));
+    }
+
+    #[test]
+    fn test_wide_characters() {
+        let output = render(
+            Some(11),
+            &["id", "name"],
+            &[
+                &[Some("1"), Some("日本語")],
+                &[Some("2"), Some("東京都庁舎")],
+            ],
+        );
+
+        assert_eq!(output, "id | name\n---+-------\n1  | 日本語\n2  | 東京…\n");
+    }
+
+    #[test]
+    fn test_control_characters_are_replaced() {
+        let output = render(None, &["v"], &[&[Some("a\nb")]]);
+        assert_eq!(output.lines().nth(2), Some("a b"));
+    }
+}
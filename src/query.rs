@@ -34,6 +34,31 @@ pub struct ObjectTree<T> {
     pub children: Vec<ObjectTree<T>>,
 }
 
+impl<T> ObjectTree<T> {
+    /// Returns an iterator over the values in this tree in depth-first order, starting with the
+    /// root.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let children: Box<dyn Iterator<Item = &T>> =
+            Box::new(self.children.iter().flat_map(|child| child.iter()));
+        std::iter::once(&self.root).chain(children)
+    }
+
+    /// Maps each value in this tree using a fallible function, preserving the tree's structure.
+    pub fn try_map<U, E, F>(self, f: &mut F) -> std::result::Result<ObjectTree<U>, E>
+    where
+        F: FnMut(T) -> std::result::Result<U, E>,
+    {
+        let root = f(self.root)?;
+        let children = self
+            .children
+            .into_iter()
+            .map(|child| child.try_map(f))
+            .collect::<std::result::Result<Vec<_>, E>>()?;
+
+        Ok(ObjectTree { root, children })
+    }
+}
+
 impl<T: fmt::Display> fmt::Display for ObjectTree<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.root)?;
@@ -78,9 +103,9 @@ impl fmt::Display for Operator {
 /// A predicate for filtering results in a query.
 #[derive(Debug)]
 pub struct Predicate<'a, T> {
-    identifier: T,
-    operator: Operator,
-    value: Literal<'a>,
+    pub identifier: T,
+    pub operator: Operator,
+    pub value: Literal<'a>,
 }
 
 impl<'a, T: fmt::Display> fmt::Display for Predicate<'a, T> {
@@ -150,11 +175,24 @@ fn parse_identifier<'a>(input: &'a str) -> ParseResult<'a, String> {
     Ok(("", input.to_string()))
 }
 
+/// Parses an object name from the input string, optionally qualified by a namespace as in
+/// `schema.object`.
+#[tracing::instrument(level = "trace", err)]
+fn parse_object_name<'a>(input: &'a str) -> ParseResult<'a, String> {
+    let (input, name) = parse_identifier(input)?;
+    let Some(input) = input.strip_prefix('.') else {
+        return Ok((input, name));
+    };
+
+    let (input, object) = parse_identifier(input)?;
+    Ok((input, format!("{}.{}", name, object)))
+}
+
 #[tracing::instrument(level = "trace", err)]
 fn parse_object_tree<'a>(input: &'a str) -> ParseResult<'a, ObjectTree<String>> {
     // Parse root identifier.
     let input = skip_whitespace(input);
-    let (input, root) = parse_identifier(input)?;
+    let (input, root) = parse_object_name(input)?;
 
     // Next character should be '>', if not, we're done.
     let input = skip_whitespace(input);
@@ -345,6 +383,15 @@ mod tests {
     //     roundtrip("a>b>c^d");
     // }
 
+    #[test]
+    fn test_query_with_namespaces() {
+        roundtrip("dbo.a>audit.b");
+
+        let query = parse("audit.users>orders").unwrap();
+        assert_eq!(query.object.root, "audit.users");
+        assert_eq!(query.object.children[0].root, "orders");
+    }
+
     #[test]
     fn test_query_with_predicates() {
         roundtrip("a>b foo=bar baz>42");
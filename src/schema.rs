@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
 use serde::{Deserialize, Serialize};
 use slotmap::{SlotMap, new_key_type};
@@ -8,22 +8,167 @@ use crate::alg::{Name, Score, Scored};
 new_key_type! { pub struct ObjectId; }
 new_key_type! { pub struct ColumnId; }
 
+/// The type of a column.
+///
+/// Lengths, precisions and scales are [`None`] when the database does not declare one, for
+/// example `varchar(max)` in SQL Server or `numeric` in PostgreSQL.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
     #[default]
     Unknown,
+    TinyInt,
+    SmallInt,
     Integer,
+    BigInt,
+    Decimal {
+        precision: Option<u32>,
+        scale: Option<u32>,
+    },
+    Real,
+    Double,
+    Char(Option<u32>),
+    VarChar(Option<u32>),
+    NChar(Option<u32>),
+    NVarChar(Option<u32>),
+    Text,
+    Date,
+    Time,
+    DateTime,
+    DateTimeOffset,
+    Boolean,
+    Binary(Option<u32>),
+    VarBinary(Option<u32>),
+    Blob,
+    Uuid,
+    Json,
+    Xml,
+
+    /// A type which is not otherwise modelled, named as the database names it.
+    Other(String),
+}
+
+impl DataType {
+    /// Maps the name of a type, as reported by a database, to a [`DataType`].
+    ///
+    /// Names are matched case insensitively and cover the common spellings used by SQL Server,
+    /// PostgreSQL, MySQL and SQLite. Drivers handle names which mean something different in their
+    /// database before falling back to this.
+    pub fn from_name(
+        name: &str,
+        length: Option<u32>,
+        precision: Option<u32>,
+        scale: Option<u32>,
+    ) -> DataType {
+        match name.to_ascii_lowercase().as_str() {
+            "tinyint" => DataType::TinyInt,
+            "smallint" | "int2" | "smallserial" => DataType::SmallInt,
+            "int" | "integer" | "int4" | "mediumint" | "serial" => DataType::Integer,
+            "bigint" | "int8" | "bigserial" => DataType::BigInt,
+            "decimal" | "numeric" | "number" => DataType::Decimal { precision, scale },
+            "real" | "float4" => DataType::Real,
+            "float" | "double" | "double precision" | "float8" => DataType::Double,
+            "char" | "character" | "bpchar" => DataType::Char(length),
+            "varchar" | "character varying" | "varchar2" => DataType::VarChar(length),
+            "nchar" => DataType::NChar(length),
+            "nvarchar" | "nvarchar2" => DataType::NVarChar(length),
+            "text" | "ntext" | "tinytext" | "mediumtext" | "longtext" | "clob" | "citext" => {
+                DataType::Text
+            }
+            "date" => DataType::Date,
+            "time" | "time without time zone" => DataType::Time,
+            "datetime"
+            | "datetime2"
+            | "smalldatetime"
+            | "timestamp"
+            | "timestamp without time zone" => DataType::DateTime,
+            "datetimeoffset" | "timestamptz" | "timestamp with time zone" => {
+                DataType::DateTimeOffset
+            }
+            "bit" | "bool" | "boolean" => DataType::Boolean,
+            "binary" => DataType::Binary(length),
+            "varbinary" => DataType::VarBinary(length),
+            "blob" | "tinyblob" | "mediumblob" | "longblob" | "bytea" | "image" => DataType::Blob,
+            "uuid" | "uniqueidentifier" => DataType::Uuid,
+            "json" | "jsonb" => DataType::Json,
+            "xml" => DataType::Xml,
+            "" => DataType::Unknown,
+            _ => DataType::Other(name.to_string()),
+        }
+    }
+
+    /// Whether values of this type are integers.
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            DataType::TinyInt | DataType::SmallInt | DataType::Integer | DataType::BigInt
+        )
+    }
+
+    /// Whether values of this type are character strings.
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            DataType::Char(_)
+                | DataType::VarChar(_)
+                | DataType::NChar(_)
+                | DataType::NVarChar(_)
+                | DataType::Text
+        )
+    }
 }
 
 impl Display for DataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let with_length =
+            |f: &mut std::fmt::Formatter<'_>, name: &str, length: &Option<u32>| match length {
+                Some(length) => write!(f, "{}({})", name, length),
+                None => write!(f, "{}", name),
+            };
+
         match self {
-            DataType::Integer => write!(f, "int"),
             DataType::Unknown => write!(f, "unknown"),
+            DataType::TinyInt => write!(f, "tinyint"),
+            DataType::SmallInt => write!(f, "smallint"),
+            DataType::Integer => write!(f, "int"),
+            DataType::BigInt => write!(f, "bigint"),
+            DataType::Decimal {
+                precision: Some(precision),
+                scale: Some(scale),
+            } => write!(f, "decimal({},{})", precision, scale),
+            DataType::Decimal { precision, .. } => with_length(f, "decimal", precision),
+            DataType::Real => write!(f, "real"),
+            DataType::Double => write!(f, "double"),
+            DataType::Char(length) => with_length(f, "char", length),
+            DataType::VarChar(length) => with_length(f, "varchar", length),
+            DataType::NChar(length) => with_length(f, "nchar", length),
+            DataType::NVarChar(length) => with_length(f, "nvarchar", length),
+            DataType::Text => write!(f, "text"),
+            DataType::Date => write!(f, "date"),
+            DataType::Time => write!(f, "time"),
+            DataType::DateTime => write!(f, "datetime"),
+            DataType::DateTimeOffset => write!(f, "datetimeoffset"),
+            DataType::Boolean => write!(f, "boolean"),
+            DataType::Binary(length) => with_length(f, "binary", length),
+            DataType::VarBinary(length) => with_length(f, "varbinary", length),
+            DataType::Blob => write!(f, "blob"),
+            DataType::Uuid => write!(f, "uuid"),
+            DataType::Json => write!(f, "json"),
+            DataType::Xml => write!(f, "xml"),
+            DataType::Other(name) => write!(f, "{}", name),
         }
     }
 }
 
+/// Describes how the values of an identity (or auto-increment) column are generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    /// The first value generated, if the database exposes it.
+    pub seed: Option<i64>,
+
+    /// The amount added to generate each subsequent value, if the database exposes it.
+    pub increment: Option<i64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Column {
     pub id: ColumnId,
@@ -31,6 +176,21 @@ pub struct Column {
     pub name: String,
     pub data_type: DataType,
     pub nullable: bool,
+
+    /// Expression used for the column's value when an insert does not give one.
+    #[serde(default)]
+    pub default: Option<String>,
+
+    #[serde(default)]
+    pub identity: Option<Identity>,
+
+    /// Expression from which the column's value is computed, for computed (generated) columns.
+    #[serde(default)]
+    pub computed: Option<String>,
+
+    /// Documentation for the column kept in the database, such as a comment.
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 impl Name for Column {
@@ -53,27 +213,145 @@ impl Scored for Column {
 }
 
 /// Models a foreign key relationship between columns in different objects.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+///
+/// A composite foreign key is stored as one `ForeignKey` per column, which share the same
+/// `constraint`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForeignKey {
+    /// Identifies the constraint the foreign key belongs to, unique within the referencing table.
+    /// This is the name of the constraint where the database exposes one.
+    #[serde(default)]
+    pub constraint: Option<String>,
+
     pub column: ColumnId,
     pub referenced_object: ObjectId,
     pub referenced_column: ColumnId,
 }
 
+/// A set of columns whose values uniquely identify a row in a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Key {
+    /// Name of the constraint, if the database exposes one.
+    pub name: Option<String>,
+
+    /// Columns making up the key, in order.
+    pub columns: Vec<ColumnId>,
+}
+
+/// An index on a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Index {
+    pub name: String,
+
+    /// Key columns of the index, in order.
+    pub columns: Vec<ColumnId>,
+
+    /// Non-key columns stored in the index, for databases which support them.
+    #[serde(default)]
+    pub included_columns: Vec<ColumnId>,
+
+    pub unique: bool,
+
+    /// Whether the table's rows are stored in the order of this index.
+    pub clustered: bool,
+
+    /// Condition restricting the rows covered by a partial (or filtered) index.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+/// The direction in which a value is passed through a parameter of a procedure or function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParameterMode {
+    In,
+    Out,
+    InOut,
+}
+
+impl ParameterMode {
+    /// Parses a parameter mode as reported by `INFORMATION_SCHEMA.PARAMETERS`.
+    pub fn from_name(name: &str) -> ParameterMode {
+        match name.to_ascii_uppercase().as_str() {
+            "OUT" => ParameterMode::Out,
+            "INOUT" => ParameterMode::InOut,
+            _ => ParameterMode::In,
+        }
+    }
+}
+
+impl Display for ParameterMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterMode::In => write!(f, "in"),
+            ParameterMode::Out => write!(f, "out"),
+            ParameterMode::InOut => write!(f, "inout"),
+        }
+    }
+}
+
+/// A parameter of a procedure or function.
+///
+/// The name and type of a parameter are stored as a column, so that parameters can be resolved
+/// by name in the same way as columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub column: ColumnId,
+    pub mode: ParameterMode,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Object {
     Table {
         id: ObjectId,
         score: Option<Score>,
+        #[serde(default)]
+        namespace: Option<String>,
         name: String,
+        #[serde(default)]
+        description: Option<String>,
         columns: Vec<ColumnId>,
         foreign_keys: Vec<ForeignKey>,
+        #[serde(default)]
+        primary_key: Option<Key>,
+        #[serde(default)]
+        unique_keys: Vec<Key>,
+        #[serde(default)]
+        indexes: Vec<Index>,
     },
 
     View {
         id: ObjectId,
         score: Option<Score>,
+        #[serde(default)]
+        namespace: Option<String>,
+        name: String,
+        #[serde(default)]
+        description: Option<String>,
+        columns: Vec<ColumnId>,
+    },
+
+    Procedure {
+        id: ObjectId,
+        score: Option<Score>,
+        namespace: Option<String>,
+        name: String,
+        description: Option<String>,
+        parameters: Vec<Parameter>,
+    },
+
+    Function {
+        id: ObjectId,
+        score: Option<Score>,
+        namespace: Option<String>,
         name: String,
+        description: Option<String>,
+        parameters: Vec<Parameter>,
+
+        /// The type of the value returned by a scalar function, or [`None`] for a table-valued
+        /// function.
+        return_type: Option<DataType>,
+
+        /// The columns of the table returned by a table-valued function.
         columns: Vec<ColumnId>,
     },
 }
@@ -81,7 +359,19 @@ pub enum Object {
 impl Name for Object {
     fn name(&self) -> &str {
         match self {
-            Object::Table { name, .. } | Object::View { name, .. } => name,
+            Object::Table { name, .. }
+            | Object::View { name, .. }
+            | Object::Procedure { name, .. }
+            | Object::Function { name, .. } => name,
+        }
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        match self {
+            Object::Table { namespace, .. }
+            | Object::View { namespace, .. }
+            | Object::Procedure { namespace, .. }
+            | Object::Function { namespace, .. } => namespace.as_deref(),
         }
     }
 }
@@ -89,22 +379,107 @@ impl Name for Object {
 impl Scored for Object {
     fn score(&self) -> Option<Score> {
         match self {
-            Object::Table { score, .. } | Object::View { score, .. } => *score,
+            Object::Table { score, .. }
+            | Object::View { score, .. }
+            | Object::Procedure { score, .. }
+            | Object::Function { score, .. } => *score,
         }
     }
 
     fn score_mut(&mut self) -> &mut Option<Score> {
         match self {
-            Object::Table { score, .. } | Object::View { score, .. } => score,
+            Object::Table { score, .. }
+            | Object::View { score, .. }
+            | Object::Procedure { score, .. }
+            | Object::Function { score, .. } => score,
         }
     }
 }
 
 impl Object {
+    pub fn id(&self) -> ObjectId {
+        match self {
+            Object::Table { id, .. }
+            | Object::View { id, .. }
+            | Object::Procedure { id, .. }
+            | Object::Function { id, .. } => *id,
+        }
+    }
+
+    /// Gets the name of this object, qualified by its namespace if it has one.
+    pub fn qualified_name(&self) -> String {
+        match self.namespace() {
+            Some(namespace) => format!("{}.{}", namespace, self.name()),
+            None => self.name().to_string(),
+        }
+    }
+
+    /// Gets the documentation for this object kept in the database, such as a comment.
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            Object::Table { description, .. }
+            | Object::View { description, .. }
+            | Object::Procedure { description, .. }
+            | Object::Function { description, .. } => description.as_deref(),
+        }
+    }
+
+    /// Gets the columns of this object, which for a function are the columns of the table it
+    /// returns.
+    pub fn columns(&self) -> &[ColumnId] {
+        match self {
+            Object::Table { columns, .. }
+            | Object::View { columns, .. }
+            | Object::Function { columns, .. } => columns,
+            Object::Procedure { .. } => &[],
+        }
+    }
+
+    pub fn parameters(&self) -> &[Parameter] {
+        match self {
+            Object::Procedure { parameters, .. } | Object::Function { parameters, .. } => {
+                parameters
+            }
+            Object::Table { .. } | Object::View { .. } => &[],
+        }
+    }
+
+    /// Whether rows can be selected from this object, as is the case for tables, views and
+    /// table-valued functions.
+    pub fn is_queryable(&self) -> bool {
+        match self {
+            Object::Table { .. } | Object::View { .. } => true,
+            Object::Function { return_type, .. } => return_type.is_none(),
+            Object::Procedure { .. } => false,
+        }
+    }
+
     pub fn foreign_keys(&self) -> &[ForeignKey] {
         match self {
             Object::Table { foreign_keys, .. } => foreign_keys,
-            Object::View { .. } => &[],
+            _ => &[],
+        }
+    }
+
+    pub fn primary_key(&self) -> Option<&Key> {
+        match self {
+            Object::Table { primary_key, .. } => primary_key.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn indexes(&self) -> &[Index] {
+        match self {
+            Object::Table { indexes, .. } => indexes,
+            _ => &[],
+        }
+    }
+
+    /// Gets the unique constraints of this object, other than its primary key.
+    pub fn unique_keys(&self) -> &[Key] {
+        match self {
+            Object::Table { unique_keys, .. } => unique_keys,
+            _ => &[],
         }
     }
 }
@@ -113,6 +488,15 @@ impl Object {
 pub struct Schema {
     pub objects: SlotMap<ObjectId, Object>,
     pub columns: SlotMap<ColumnId, Column>,
+
+    /// When this schema was fetched from the database, in seconds since the Unix epoch, or 0 if
+    /// unknown.
+    #[serde(default)]
+    pub fetched_at: u64,
+
+    /// The database's fingerprint when this schema was fetched, if it has one.
+    #[serde(default)]
+    pub fingerprint: Option<String>,
 }
 
 impl Schema {
@@ -123,4 +507,333 @@ impl Schema {
             .values()
             .filter(move |o| o.foreign_keys().iter().any(|fk| fk.referenced_object == id))
     }
+
+    /// Copies the scores of objects and columns from another version of this schema, such as the
+    /// one in the cache when the schema is fetched again from the database. Where both versions
+    /// have a score, the most recently updated one is kept, preferring `old`'s when both were
+    /// updated at the same time.
+    ///
+    /// Objects are matched by qualified name and columns (including parameters) by name within
+    /// their object, since IDs are not stable between fetches.
+    pub fn merge_scores(&mut self, old: &Schema) {
+        fn merge(score: &mut Option<Score>, other: Option<Score>) {
+            if other
+                .is_some_and(|other| score.is_none_or(|score| other.timestamp >= score.timestamp))
+            {
+                *score = other;
+            }
+        }
+
+        let old_objects = old
+            .objects
+            .values()
+            .map(|obj| (obj.qualified_name(), obj))
+            .collect::<HashMap<_, _>>();
+
+        for obj in self.objects.values_mut() {
+            let Some(old_obj) = old_objects.get(&obj.qualified_name()) else {
+                continue;
+            };
+            merge(obj.score_mut(), old_obj.score());
+
+            let old_columns = old_obj
+                .columns()
+                .iter()
+                .chain(old_obj.parameters().iter().map(|p| &p.column))
+                .filter_map(|id| old.columns.get(*id))
+                .map(|column| (column.name.as_str(), column.score))
+                .collect::<HashMap<_, _>>();
+
+            for id in obj
+                .columns()
+                .iter()
+                .chain(obj.parameters().iter().map(|p| &p.column))
+            {
+                if let Some(column) = self.columns.get_mut(*id)
+                    && let Some(score) = old_columns.get(column.name.as_str())
+                {
+                    merge(&mut column.score, *score);
+                }
+            }
+        }
+    }
+}
+
+/// Builds the schemas used by tests, so that each test only spells out the parts of an object it
+/// depends on.
+#[cfg(test)]
+pub mod builder {
+    use super::*;
+
+    /// Builds an object along with its columns and parameters.
+    ///
+    /// Columns and parameters are nullable and of unknown type unless given otherwise.
+    #[derive(Default)]
+    pub struct ObjectBuilder {
+        namespace: Option<String>,
+        name: String,
+        columns: Vec<(String, DataType, bool)>,
+        parameters: Vec<(String, DataType, ParameterMode)>,
+    }
+
+    impl ObjectBuilder {
+        pub fn new(name: &str) -> Self {
+            ObjectBuilder {
+                name: name.to_string(),
+                ..ObjectBuilder::default()
+            }
+        }
+
+        pub fn namespace(mut self, namespace: &str) -> Self {
+            self.namespace = Some(namespace.to_string());
+            self
+        }
+
+        pub fn columns(mut self, names: &[&str]) -> Self {
+            for name in names {
+                self = self.column(name, DataType::Unknown, true);
+            }
+            self
+        }
+
+        pub fn column(mut self, name: &str, data_type: DataType, nullable: bool) -> Self {
+            self.columns.push((name.to_string(), data_type, nullable));
+            self
+        }
+
+        pub fn parameters(mut self, names: &[&str], mode: ParameterMode) -> Self {
+            for name in names {
+                self = self.parameter(name, DataType::Unknown, mode);
+            }
+            self
+        }
+
+        pub fn parameter(mut self, name: &str, data_type: DataType, mode: ParameterMode) -> Self {
+            self.parameters.push((name.to_string(), data_type, mode));
+            self
+        }
+
+        pub fn table(self, schema: &mut Schema) -> ObjectId {
+            let (namespace, name, columns, _) = self.insert_columns(schema);
+            schema.objects.insert_with_key(|id| Object::Table {
+                id,
+                score: None,
+                namespace,
+                name,
+                description: None,
+                columns,
+                foreign_keys: Vec::new(),
+                primary_key: None,
+                unique_keys: Vec::new(),
+                indexes: Vec::new(),
+            })
+        }
+
+        pub fn view(self, schema: &mut Schema) -> ObjectId {
+            let (namespace, name, columns, _) = self.insert_columns(schema);
+            schema.objects.insert_with_key(|id| Object::View {
+                id,
+                score: None,
+                namespace,
+                name,
+                description: None,
+                columns,
+            })
+        }
+
+        pub fn procedure(self, schema: &mut Schema) -> ObjectId {
+            let (namespace, name, _, parameters) = self.insert_columns(schema);
+            schema.objects.insert_with_key(|id| Object::Procedure {
+                id,
+                score: None,
+                namespace,
+                name,
+                description: None,
+                parameters,
+            })
+        }
+
+        pub fn function(self, schema: &mut Schema) -> ObjectId {
+            let (namespace, name, columns, parameters) = self.insert_columns(schema);
+            schema.objects.insert_with_key(|id| Object::Function {
+                id,
+                score: None,
+                namespace,
+                name,
+                description: None,
+                parameters,
+                return_type: None,
+                columns,
+            })
+        }
+
+        /// Adds the columns and parameters to `schema`, returning them along with the namespace
+        /// and name of the object.
+        fn insert_columns(
+            self,
+            schema: &mut Schema,
+        ) -> (Option<String>, String, Vec<ColumnId>, Vec<Parameter>) {
+            let mut insert = |name: String, data_type: DataType, nullable: bool| {
+                schema.columns.insert_with_key(|id| Column {
+                    id,
+                    score: None,
+                    name,
+                    data_type,
+                    nullable,
+                    default: None,
+                    identity: None,
+                    computed: None,
+                    description: None,
+                })
+            };
+
+            let columns = self
+                .columns
+                .into_iter()
+                .map(|(name, data_type, nullable)| insert(name, data_type, nullable))
+                .collect();
+            let parameters = self
+                .parameters
+                .into_iter()
+                .map(|(name, data_type, mode)| Parameter {
+                    column: insert(name, data_type, true),
+                    mode,
+                })
+                .collect();
+
+            (self.namespace, self.name, columns, parameters)
+        }
+    }
+
+    /// Adds a foreign key from `columns` of `object` to `referenced_columns` of
+    /// `referenced_object`, stored as one [`ForeignKey`] per column of a single constraint.
+    pub fn foreign_key(
+        schema: &mut Schema,
+        object: ObjectId,
+        columns: &[&str],
+        referenced_object: ObjectId,
+        referenced_columns: &[&str],
+    ) {
+        let find = |schema: &Schema, object: ObjectId, name: &str| {
+            *schema.objects[object]
+                .columns()
+                .iter()
+                .find(|id| schema.columns[**id].name == name)
+                .unwrap()
+        };
+
+        let constraint = format!("fk_{}_{}", schema.objects[object].name(), columns.join("_"));
+        let fks = columns
+            .iter()
+            .zip(referenced_columns)
+            .map(|(column, referenced_column)| ForeignKey {
+                constraint: Some(constraint.clone()),
+                column: find(schema, object, column),
+                referenced_object,
+                referenced_column: find(schema, referenced_object, referenced_column),
+            })
+            .collect::<Vec<_>>();
+
+        let Object::Table { foreign_keys, .. } = &mut schema.objects[object] else {
+            panic!("not a table");
+        };
+        foreign_keys.extend(fks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builder::ObjectBuilder, *};
+
+    #[test]
+    fn test_data_type_from_name() {
+        assert_eq!(
+            DataType::from_name("NVARCHAR", Some(50), None, None),
+            DataType::NVarChar(Some(50))
+        );
+        assert_eq!(
+            DataType::from_name("numeric", None, Some(10), Some(2)),
+            DataType::Decimal {
+                precision: Some(10),
+                scale: Some(2)
+            }
+        );
+        assert_eq!(
+            DataType::from_name("timestamp with time zone", None, None, None),
+            DataType::DateTimeOffset
+        );
+        assert_eq!(
+            DataType::from_name("geography", None, None, None),
+            DataType::Other("geography".to_string())
+        );
+    }
+
+    #[test]
+    fn test_merge_scores() {
+        let mut old = Schema::default();
+        let users = ObjectBuilder::new("users")
+            .columns(&["id", "name"])
+            .table(&mut old);
+        let orders = ObjectBuilder::new("orders")
+            .columns(&["id"])
+            .table(&mut old);
+        old.objects[users].score_mut().replace(Score::new(4.0));
+        let name = old.objects[users].columns()[1];
+        old.columns[name].score = Some(Score::new(2.0));
+        old.objects[orders].score_mut().replace(Score::new(1.0));
+
+        // The refreshed schema has a new column and has lost a table.
+        let mut new = Schema::default();
+        let users = ObjectBuilder::new("users")
+            .columns(&["id", "email", "name"])
+            .table(&mut new);
+        let audit = ObjectBuilder::new("audit").columns(&["id"]).table(&mut new);
+        new.merge_scores(&old);
+
+        let score = |score: Option<Score>| score.map(|s| s.value);
+        assert_eq!(score(new.objects[users].score()), Some(4.0));
+        assert_eq!(score(new.objects[audit].score()), None);
+
+        let columns = new.objects[users]
+            .columns()
+            .iter()
+            .map(|id| score(new.columns[*id].score))
+            .collect::<Vec<_>>();
+        assert_eq!(columns, [None, None, Some(2.0)]);
+    }
+
+    #[test]
+    fn test_merge_scores_keeps_newest() {
+        let score = |value, timestamp| Some(Score { value, timestamp });
+
+        let mut old = Schema::default();
+        let users = ObjectBuilder::new("users").columns(&[]).table(&mut old);
+        let orders = ObjectBuilder::new("orders").columns(&[]).table(&mut old);
+        *old.objects[users].score_mut() = score(1.0, 100);
+        *old.objects[orders].score_mut() = score(8.0, 300);
+
+        let mut new = Schema::default();
+        let users = ObjectBuilder::new("users").columns(&[]).table(&mut new);
+        let orders = ObjectBuilder::new("orders").columns(&[]).table(&mut new);
+        *new.objects[users].score_mut() = score(2.0, 200);
+        *new.objects[orders].score_mut() = score(4.0, 200);
+        new.merge_scores(&old);
+
+        assert_eq!(new.objects[users].score().map(|s| s.value), Some(2.0));
+        assert_eq!(new.objects[orders].score().map(|s| s.value), Some(8.0));
+    }
+
+    #[test]
+    fn test_data_type_display() {
+        assert_eq!(DataType::NVarChar(Some(50)).to_string(), "nvarchar(50)");
+        assert_eq!(DataType::VarChar(None).to_string(), "varchar");
+        assert_eq!(
+            DataType::Decimal {
+                precision: Some(10),
+                scale: Some(2)
+            }
+            .to_string(),
+            "decimal(10,2)"
+        );
+    }
 }
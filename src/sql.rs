@@ -1,5 +1,19 @@
 #![allow(dead_code)]
 
+mod ansi;
+mod mysql;
+mod oracle;
+mod postgres;
+mod sqlite;
+mod tsql;
+
+pub use ansi::AnsiDialect;
+pub use mysql::MySqlDialect;
+pub use oracle::OracleDialect;
+pub use postgres::PostgresDialect;
+pub use sqlite::SqliteDialect;
+pub use tsql::TSql;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SqlOp {
     Eq,
@@ -11,6 +25,8 @@ pub enum SqlOp {
     And,
     Or,
     Like,
+    /// Case sensitive pattern match using Unix style wildcards. Only supported by SQLite.
+    Glob,
     IsNull,
     IsNotNull,
 }
@@ -24,8 +40,10 @@ pub struct SqlFieldRef {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SqlExpr {
     Null,
+    BoolLiteral(bool),
     StringLiteral(String),
     IntLiteral(i64),
+    Param(SqlParam),
     Ref(SqlFieldRef),
     BinaryOp {
         left: Box<SqlExpr>,
@@ -34,9 +52,25 @@ pub enum SqlExpr {
     },
 }
 
+/// A value bound to a placeholder in a SQL statement.
+///
+/// Values supplied by the user should always be passed as parameters rather than literals so that
+/// they never become part of the SQL text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlParam {
+    String(String),
+    Int(i64),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SqlObjectRef {
+    /// The schema (or namespace) containing the object, if it must be qualified.
+    pub schema: Option<String>,
     pub object: String,
+
+    /// Arguments passed to the object when it is a table-valued function, or [`None`] if it is
+    /// not a function.
+    pub arguments: Option<Vec<SqlExpr>>,
     pub alias: String,
 }
 
@@ -66,22 +100,60 @@ pub struct SqlQuery {
     pub projection: Vec<SqlFieldRef>,
     pub from: SqlFromClause,
     pub where_clause: Option<SqlExpr>,
+
+    /// Maximum number of rows returned by the query.
+    pub limit: Option<u64>,
+
+    /// Number of rows skipped before any are returned.
+    pub offset: Option<u64>,
+}
+
+/// A rendered SQL statement along with the parameters bound to its placeholders, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlStatement {
+    pub sql: String,
+    pub params: Vec<SqlParam>,
 }
 
 pub trait SqlDialect {
-    fn query(&self, query: &SqlQuery) -> String {
+    fn query(&self, query: &SqlQuery) -> SqlStatement {
+        let mut params = Vec::new();
         let mut sql = String::new();
         sql.push_str("SELECT ");
+        if let Some(top) = self.top(query.limit, query.offset) {
+            sql.push_str(&top);
+            sql.push(' ');
+        }
         sql.push_str(&self.projection(&query.projection));
         sql.push_str(" FROM ");
-        sql.push_str(&self.from(&query.from));
+        sql.push_str(&self.from(&query.from, &mut params));
 
         if let Some(ref where_expr) = query.where_clause {
             sql.push_str(" WHERE ");
-            sql.push_str(&self.expr(where_expr));
+            sql.push_str(&self.expr(where_expr, &mut params));
         }
 
-        sql
+        if let Some(limit) = self.limit(query.limit, query.offset) {
+            sql.push(' ');
+            sql.push_str(&limit);
+        }
+
+        SqlStatement { sql, params }
+    }
+
+    /// Renders a row limit placed directly after `SELECT`, for dialects which use `TOP`.
+    fn top(&self, _limit: Option<u64>, _offset: Option<u64>) -> Option<String> {
+        None
+    }
+
+    /// Renders a row limit and offset placed at the end of the query.
+    fn limit(&self, limit: Option<u64>, offset: Option<u64>) -> Option<String> {
+        match (limit, offset) {
+            (None, None) => None,
+            (Some(limit), None) => Some(format!("LIMIT {}", limit)),
+            (None, Some(offset)) => Some(format!("OFFSET {}", offset)),
+            (Some(limit), Some(offset)) => Some(format!("LIMIT {} OFFSET {}", limit, offset)),
+        }
     }
 
     fn projection(&self, projection: &[SqlFieldRef]) -> String {
@@ -92,17 +164,17 @@ pub trait SqlDialect {
             .join(", ")
     }
 
-    fn from(&self, from: &SqlFromClause) -> String {
-        let mut sql = self.object_ref(&from.object);
+    fn from(&self, from: &SqlFromClause, params: &mut Vec<SqlParam>) -> String {
+        let mut sql = self.object_ref(&from.object, params);
         for join in &from.joins {
             sql.push(' ');
-            sql.push_str(&self.join(join));
+            sql.push_str(&self.join(join, params));
         }
 
         sql
     }
 
-    fn join(&self, join: &SqlJoin) -> String {
+    fn join(&self, join: &SqlJoin, params: &mut Vec<SqlParam>) -> String {
         let join_type = match join.join_type {
             SqlJoinType::Inner => "INNER JOIN",
             SqlJoinType::Left => "LEFT JOIN",
@@ -110,21 +182,28 @@ pub trait SqlDialect {
             SqlJoinType::Outer => "OUTER JOIN",
         };
 
+        let object = self.object_ref(&join.object, params);
         format!(
             "{} {} ON {}",
             join_type,
-            self.object_ref(&join.object),
-            self.expr(&join.on)
+            object,
+            self.expr(&join.on, params)
         )
     }
 
-    fn expr(&self, expr: &SqlExpr) -> String {
+    /// Renders an expression, appending the values of any parameters it contains to `params`.
+    fn expr(&self, expr: &SqlExpr, params: &mut Vec<SqlParam>) -> String {
         match expr {
             SqlExpr::Null => "NULL".to_string(),
-            SqlExpr::StringLiteral(s) => format!("'{}'", s),
+            SqlExpr::BoolLiteral(b) => self.bool_literal(*b),
+            SqlExpr::StringLiteral(s) => self.string_literal(s),
             SqlExpr::IntLiteral(i) => i.to_string(),
+            SqlExpr::Param(param) => {
+                params.push(param.clone());
+                self.placeholder(params.len())
+            }
             SqlExpr::Ref(field_ref) => self.field_ref(field_ref),
-            SqlExpr::BinaryOp { left, op, right } => self.binary_op(left, *op, right),
+            SqlExpr::BinaryOp { left, op, right } => self.binary_op(left, *op, right, params),
         }
     }
 
@@ -136,8 +215,20 @@ pub trait SqlDialect {
         )
     }
 
-    fn binary_op(&self, left: &SqlExpr, op: SqlOp, right: &SqlExpr) -> String {
-        let op_str = match op {
+    fn binary_op(
+        &self,
+        left: &SqlExpr,
+        op: SqlOp,
+        right: &SqlExpr,
+        params: &mut Vec<SqlParam>,
+    ) -> String {
+        let left = self.expr(left, params);
+        let right = self.expr(right, params);
+        format!("{} {} {}", left, self.operator(op), right)
+    }
+
+    fn operator(&self, op: SqlOp) -> &'static str {
+        match op {
             SqlOp::Eq => "=",
             SqlOp::Neq => "<>",
             SqlOp::Gt => ">",
@@ -147,24 +238,65 @@ pub trait SqlDialect {
             SqlOp::And => "AND",
             SqlOp::Or => "OR",
             SqlOp::Like => "LIKE",
+            SqlOp::Glob => "GLOB",
             SqlOp::IsNull => "IS NULL",
             SqlOp::IsNotNull => "IS NOT NULL",
-        };
-
-        format!("{} {} {}", self.expr(left), op_str, self.expr(right))
+        }
     }
 
-    fn object_ref(&self, object: &SqlObjectRef) -> String {
+    fn object_ref(&self, object: &SqlObjectRef, params: &mut Vec<SqlParam>) -> String {
         format!(
             "{} AS {}",
-            self.identifier(&object.object),
+            self.object_source(object, params),
             self.identifier(&object.alias)
         )
     }
 
+    /// Renders the object being selected from, which is either its name or, for a table-valued
+    /// function, a call to it.
+    fn object_source(&self, object: &SqlObjectRef, params: &mut Vec<SqlParam>) -> String {
+        let name = self.object_name(object);
+        let Some(arguments) = &object.arguments else {
+            return name;
+        };
+
+        let arguments = arguments
+            .iter()
+            .map(|arg| self.expr(arg, params))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}({})", name, arguments)
+    }
+
+    /// Renders the name of an object, qualified by its schema if it has one.
+    fn object_name(&self, object: &SqlObjectRef) -> String {
+        match &object.schema {
+            Some(schema) => format!(
+                "{}.{}",
+                self.identifier(schema),
+                self.identifier(&object.object)
+            ),
+            None => self.identifier(&object.object),
+        }
+    }
+
     fn identifier(&self, ident: &str) -> String {
         ident.to_string()
     }
+
+    fn bool_literal(&self, b: bool) -> String {
+        if b { "TRUE" } else { "FALSE" }.to_string()
+    }
+
+    /// Renders a string literal, escaping any embedded quotes.
+    fn string_literal(&self, s: &str) -> String {
+        format!("'{}'", s.replace('\'', "''"))
+    }
+
+    /// Renders the placeholder for the parameter at a given 1-based index.
+    fn placeholder(&self, _index: usize) -> String {
+        "?".to_string()
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy)]
@@ -191,7 +323,9 @@ mod tests {
             ],
             from: SqlFromClause {
                 object: SqlObjectRef {
+                    schema: None,
                     object: "users".to_string(),
+                    arguments: None,
                     alias: "u".to_string(),
                 },
                 joins: vec![],
@@ -204,13 +338,16 @@ mod tests {
                 op: SqlOp::Eq,
                 right: Box::new(SqlExpr::IntLiteral(1)),
             }),
+            limit: None,
+            offset: None,
         };
 
-        let sql = Sql.query(&query);
+        let statement = Sql.query(&query);
         assert_eq!(
-            sql,
+            statement.sql,
             "SELECT u.id, u.name FROM users AS u WHERE u.active = 1"
         );
+        assert!(statement.params.is_empty());
     }
 
     #[test]
@@ -228,13 +365,17 @@ mod tests {
             ],
             from: SqlFromClause {
                 object: SqlObjectRef {
+                    schema: None,
                     object: "users".to_string(),
+                    arguments: None,
                     alias: "u".to_string(),
                 },
                 joins: vec![SqlJoin {
                     join_type: SqlJoinType::Left,
                     object: SqlObjectRef {
+                        schema: None,
                         object: "posts".to_string(),
+                        arguments: None,
                         alias: "p".to_string(),
                     },
                     on: SqlExpr::BinaryOp {
@@ -251,12 +392,108 @@ mod tests {
                 }],
             },
             where_clause: None,
+            limit: None,
+            offset: None,
         };
 
-        let sql = Sql.query(&query);
+        let statement = Sql.query(&query);
         assert_eq!(
-            sql,
+            statement.sql,
             "SELECT u.id, p.title FROM users AS u LEFT JOIN posts AS p ON u.id = p.user_id"
         );
     }
+
+    #[test]
+    fn test_query_with_params() {
+        let field = |field: &str| {
+            Box::new(SqlExpr::Ref(SqlFieldRef {
+                object: "u".to_string(),
+                field: field.to_string(),
+            }))
+        };
+
+        let query = SqlQuery {
+            projection: vec![SqlFieldRef {
+                object: "u".to_string(),
+                field: "id".to_string(),
+            }],
+            from: SqlFromClause {
+                object: SqlObjectRef {
+                    schema: None,
+                    object: "users".to_string(),
+                    arguments: None,
+                    alias: "u".to_string(),
+                },
+                joins: vec![],
+            },
+            where_clause: Some(SqlExpr::BinaryOp {
+                left: Box::new(SqlExpr::BinaryOp {
+                    left: field("name"),
+                    op: SqlOp::Eq,
+                    right: Box::new(SqlExpr::Param(SqlParam::String("o'brien".to_string()))),
+                }),
+                op: SqlOp::And,
+                right: Box::new(SqlExpr::BinaryOp {
+                    left: field("age"),
+                    op: SqlOp::Gt,
+                    right: Box::new(SqlExpr::Param(SqlParam::Int(30))),
+                }),
+            }),
+            limit: Some(10),
+            offset: None,
+        };
+
+        let statement = Sql.query(&query);
+        assert_eq!(
+            statement.sql,
+            "SELECT u.id FROM users AS u WHERE u.name = ? AND u.age > ? LIMIT 10"
+        );
+        assert_eq!(
+            statement.params,
+            [SqlParam::String("o'brien".to_string()), SqlParam::Int(30)]
+        );
+    }
+
+    #[test]
+    fn test_query_from_function() {
+        let query = SqlQuery {
+            projection: vec![SqlFieldRef {
+                object: "o".to_string(),
+                field: "id".to_string(),
+            }],
+            from: SqlFromClause {
+                object: SqlObjectRef {
+                    schema: Some("sales".to_string()),
+                    object: "orders_for".to_string(),
+                    arguments: Some(vec![SqlExpr::Param(SqlParam::Int(7))]),
+                    alias: "o".to_string(),
+                },
+                joins: vec![],
+            },
+            where_clause: Some(SqlExpr::BinaryOp {
+                left: Box::new(SqlExpr::Ref(SqlFieldRef {
+                    object: "o".to_string(),
+                    field: "total".to_string(),
+                })),
+                op: SqlOp::Gt,
+                right: Box::new(SqlExpr::Param(SqlParam::Int(100))),
+            }),
+            limit: None,
+            offset: None,
+        };
+
+        let statement = Sql.query(&query);
+        assert_eq!(
+            statement.sql,
+            "SELECT o.id FROM sales.orders_for(?) AS o WHERE o.total > ?"
+        );
+        assert_eq!(statement.params, [SqlParam::Int(7), SqlParam::Int(100)]);
+    }
+
+    #[test]
+    fn test_string_literal_is_escaped() {
+        let mut params = Vec::new();
+        let sql = Sql.expr(&SqlExpr::StringLiteral("it's".to_string()), &mut params);
+        assert_eq!(sql, "'it''s'");
+    }
 }
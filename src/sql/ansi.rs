@@ -0,0 +1,44 @@
+use crate::sql::SqlDialect;
+
+/// The dialect described by the SQL standard.
+///
+/// Identifiers are quoted with double quotes and row limits are rendered using `OFFSET ... FETCH`.
+/// This is understood by DB2 and Snowflake, and is used for databases without a dialect of their
+/// own.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct AnsiDialect;
+
+impl SqlDialect for AnsiDialect {
+    fn limit(&self, limit: Option<u64>, offset: Option<u64>) -> Option<String> {
+        match (limit, offset) {
+            (None, None) => None,
+            (Some(limit), None) => Some(format!("FETCH FIRST {} ROWS ONLY", limit)),
+            (None, Some(offset)) => Some(format!("OFFSET {} ROWS", offset)),
+            (Some(limit), Some(offset)) => Some(format!(
+                "OFFSET {} ROWS FETCH NEXT {} ROWS ONLY",
+                offset, limit
+            )),
+        }
+    }
+
+    fn identifier(&self, ident: &str) -> String {
+        format!("\"{}\"", ident.replace('"', "\"\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit() {
+        assert_eq!(
+            AnsiDialect.limit(Some(5), None).as_deref(),
+            Some("FETCH FIRST 5 ROWS ONLY")
+        );
+        assert_eq!(
+            AnsiDialect.limit(Some(5), Some(10)).as_deref(),
+            Some("OFFSET 10 ROWS FETCH NEXT 5 ROWS ONLY")
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::sql::SqlDialect;
+
+/// The MySQL dialect, also used for MariaDB.
+///
+/// Identifiers are quoted with backticks and row limits are rendered using `LIMIT`/`OFFSET`.
+/// Backslashes are escape characters within MySQL string literals, so they are escaped along with
+/// quotes.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct MySqlDialect;
+
+impl SqlDialect for MySqlDialect {
+    fn limit(&self, limit: Option<u64>, offset: Option<u64>) -> Option<String> {
+        // MySQL only accepts OFFSET as part of a LIMIT clause, so the largest possible limit is
+        // used when rows are skipped without a limit.
+        match (limit, offset) {
+            (None, None) => None,
+            (Some(limit), None) => Some(format!("LIMIT {}", limit)),
+            (None, Some(offset)) => Some(format!("LIMIT {} OFFSET {}", u64::MAX, offset)),
+            (Some(limit), Some(offset)) => Some(format!("LIMIT {} OFFSET {}", limit, offset)),
+        }
+    }
+
+    fn identifier(&self, ident: &str) -> String {
+        format!("`{}`", ident.replace('`', "``"))
+    }
+
+    fn string_literal(&self, s: &str) -> String {
+        format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{SqlExpr, SqlFieldRef, SqlFromClause, SqlObjectRef, SqlQuery};
+
+    fn query(limit: Option<u64>, offset: Option<u64>) -> SqlQuery {
+        SqlQuery {
+            projection: vec![SqlFieldRef {
+                object: "t0".to_string(),
+                field: "order`id".to_string(),
+            }],
+            from: SqlFromClause {
+                object: SqlObjectRef {
+                    schema: None,
+                    object: "orders".to_string(),
+                    arguments: None,
+                    alias: "t0".to_string(),
+                },
+                joins: vec![],
+            },
+            where_clause: None,
+            limit,
+            offset,
+        }
+    }
+
+    #[test]
+    fn test_quoting_and_limit() {
+        let statement = MySqlDialect.query(&query(Some(10), None));
+        assert_eq!(
+            statement.sql,
+            "SELECT `t0`.`order``id` FROM `orders` AS `t0` LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_offset_without_limit() {
+        let statement = MySqlDialect.query(&query(None, Some(5)));
+        assert!(
+            statement
+                .sql
+                .ends_with("LIMIT 18446744073709551615 OFFSET 5")
+        );
+    }
+
+    #[test]
+    fn test_string_literal() {
+        let mut params = Vec::new();
+        let sql = MySqlDialect.expr(&SqlExpr::StringLiteral("a\\'b".to_string()), &mut params);
+        assert_eq!(sql, "'a\\\\''b'");
+    }
+}
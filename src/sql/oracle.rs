@@ -0,0 +1,64 @@
+use crate::sql::{AnsiDialect, SqlDialect, SqlObjectRef, SqlParam};
+
+/// The Oracle dialect.
+///
+/// This follows the [`AnsiDialect`], except that Oracle does not accept `AS` before a table alias
+/// and has no boolean literals, so booleans are written as integers.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct OracleDialect;
+
+impl SqlDialect for OracleDialect {
+    fn limit(&self, limit: Option<u64>, offset: Option<u64>) -> Option<String> {
+        AnsiDialect.limit(limit, offset)
+    }
+
+    fn object_ref(&self, object: &SqlObjectRef, params: &mut Vec<SqlParam>) -> String {
+        format!(
+            "{} {}",
+            self.object_source(object, params),
+            self.identifier(&object.alias)
+        )
+    }
+
+    fn identifier(&self, ident: &str) -> String {
+        AnsiDialect.identifier(ident)
+    }
+
+    fn bool_literal(&self, b: bool) -> String {
+        if b { "1" } else { "0" }.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{SqlFieldRef, SqlFromClause, SqlQuery};
+
+    #[test]
+    fn test_query() {
+        let query = SqlQuery {
+            projection: vec![SqlFieldRef {
+                object: "t0".to_string(),
+                field: "ID".to_string(),
+            }],
+            from: SqlFromClause {
+                object: SqlObjectRef {
+                    schema: None,
+                    object: "USERS".to_string(),
+                    arguments: None,
+                    alias: "t0".to_string(),
+                },
+                joins: vec![],
+            },
+            where_clause: None,
+            limit: Some(5),
+            offset: None,
+        };
+
+        let statement = OracleDialect.query(&query);
+        assert_eq!(
+            statement.sql,
+            r#"SELECT "t0"."ID" FROM "USERS" "t0" FETCH FIRST 5 ROWS ONLY"#
+        );
+    }
+}
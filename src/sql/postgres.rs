@@ -0,0 +1,115 @@
+use crate::sql::{SqlDialect, SqlFieldRef};
+
+/// The PostgreSQL dialect.
+///
+/// Identifiers are quoted with double quotes and row limits are rendered using `LIMIT`/`OFFSET`.
+/// Projected columns are cast to `text` so that values of any type can be read back from the
+/// result.
+#[derive(Debug, Clone, Copy)]
+pub struct PostgresDialect {
+    /// Whether parameters use numbered (`$n`) placeholders rather than `?`.
+    numbered_placeholders: bool,
+}
+
+impl PostgresDialect {
+    /// The dialect used by native connections, which use numbered placeholders.
+    pub const NATIVE: PostgresDialect = PostgresDialect {
+        numbered_placeholders: true,
+    };
+
+    /// The dialect used through ODBC, where the driver translates `?` placeholders itself.
+    pub const ODBC: PostgresDialect = PostgresDialect {
+        numbered_placeholders: false,
+    };
+}
+
+impl SqlDialect for PostgresDialect {
+    fn projection(&self, projection: &[SqlFieldRef]) -> String {
+        projection
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}::text AS {}",
+                    self.field_ref(field),
+                    self.identifier(&field.field)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn identifier(&self, ident: &str) -> String {
+        format!("\"{}\"", ident.replace('"', "\"\""))
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        if self.numbered_placeholders {
+            format!("${}", index)
+        } else {
+            "?".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{SqlExpr, SqlFromClause, SqlObjectRef, SqlOp, SqlParam, SqlQuery};
+
+    fn field(field: &str) -> SqlFieldRef {
+        SqlFieldRef {
+            object: "t0".to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_query() {
+        let query = SqlQuery {
+            projection: vec![field("id"), field("Name")],
+            from: SqlFromClause {
+                object: SqlObjectRef {
+                    schema: None,
+                    object: "users".to_string(),
+                    arguments: None,
+                    alias: "t0".to_string(),
+                },
+                joins: vec![],
+            },
+            where_clause: Some(SqlExpr::BinaryOp {
+                left: Box::new(SqlExpr::BinaryOp {
+                    left: Box::new(SqlExpr::Ref(field("Name"))),
+                    op: SqlOp::Eq,
+                    right: Box::new(SqlExpr::Param(SqlParam::String("alice".to_string()))),
+                }),
+                op: SqlOp::And,
+                right: Box::new(SqlExpr::BinaryOp {
+                    left: Box::new(SqlExpr::Ref(field("id"))),
+                    op: SqlOp::Gt,
+                    right: Box::new(SqlExpr::Param(SqlParam::Int(3))),
+                }),
+            }),
+            limit: Some(10),
+            offset: Some(20),
+        };
+
+        let statement = PostgresDialect::NATIVE.query(&query);
+        assert_eq!(
+            statement.sql,
+            r#"SELECT "t0"."id"::text AS "id", "t0"."Name"::text AS "Name" FROM "users" AS "t0" WHERE "t0"."Name" = $1 AND "t0"."id" > $2 LIMIT 10 OFFSET 20"#
+        );
+        assert_eq!(statement.params.len(), 2);
+
+        let statement = PostgresDialect::ODBC.query(&query);
+        assert!(
+            statement
+                .sql
+                .contains(r#"WHERE "t0"."Name" = ? AND "t0"."id" > ?"#)
+        );
+    }
+
+    #[test]
+    fn test_identifier_escaping() {
+        assert_eq!(PostgresDialect::NATIVE.identifier("a\"b"), "\"a\"\"b\"");
+    }
+}
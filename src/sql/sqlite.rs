@@ -0,0 +1,125 @@
+use crate::sql::{SqlDialect, SqlExpr, SqlOp, SqlParam};
+
+/// The SQLite dialect.
+///
+/// Identifiers are quoted with double quotes and row limits are rendered using `LIMIT`/`OFFSET`.
+/// SQLite has no boolean type, so booleans are written as integers. `LIKE` patterns use a
+/// backslash escape so that literal `%` and `_` characters can be matched, and `GLOB` is
+/// available for case sensitive matching.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct SqliteDialect;
+
+impl SqlDialect for SqliteDialect {
+    fn limit(&self, limit: Option<u64>, offset: Option<u64>) -> Option<String> {
+        // SQLite only accepts OFFSET as part of a LIMIT clause, where a negative limit means
+        // there is no limit.
+        match (limit, offset) {
+            (None, None) => None,
+            (Some(limit), None) => Some(format!("LIMIT {}", limit)),
+            (None, Some(offset)) => Some(format!("LIMIT -1 OFFSET {}", offset)),
+            (Some(limit), Some(offset)) => Some(format!("LIMIT {} OFFSET {}", limit, offset)),
+        }
+    }
+
+    fn binary_op(
+        &self,
+        left: &SqlExpr,
+        op: SqlOp,
+        right: &SqlExpr,
+        params: &mut Vec<SqlParam>,
+    ) -> String {
+        let left = self.expr(left, params);
+        let right = self.expr(right, params);
+        match op {
+            SqlOp::Like => format!("{} LIKE {} ESCAPE '\\'", left, right),
+            op => format!("{} {} {}", left, self.operator(op), right),
+        }
+    }
+
+    fn bool_literal(&self, b: bool) -> String {
+        if b { "1" } else { "0" }.to_string()
+    }
+
+    fn identifier(&self, ident: &str) -> String {
+        format!("\"{}\"", ident.replace('"', "\"\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{SqlFieldRef, SqlFromClause, SqlObjectRef, SqlQuery};
+
+    fn query(where_clause: Option<SqlExpr>, limit: Option<u64>, offset: Option<u64>) -> SqlQuery {
+        SqlQuery {
+            projection: vec![SqlFieldRef {
+                object: "t0".to_string(),
+                field: "full name".to_string(),
+            }],
+            from: SqlFromClause {
+                object: SqlObjectRef {
+                    schema: None,
+                    object: "user \"accounts\"".to_string(),
+                    arguments: None,
+                    alias: "t0".to_string(),
+                },
+                joins: vec![],
+            },
+            where_clause,
+            limit,
+            offset,
+        }
+    }
+
+    #[test]
+    fn test_quoting_and_limit() {
+        let statement = SqliteDialect.query(&query(None, Some(10), Some(20)));
+        assert_eq!(
+            statement.sql,
+            r#"SELECT "t0"."full name" FROM "user ""accounts""" AS "t0" LIMIT 10 OFFSET 20"#
+        );
+    }
+
+    #[test]
+    fn test_offset_without_limit() {
+        let statement = SqliteDialect.query(&query(None, None, Some(5)));
+        assert!(statement.sql.ends_with("LIMIT -1 OFFSET 5"));
+    }
+
+    #[test]
+    fn test_like_and_glob() {
+        let op = |op, pattern: &str| {
+            Some(SqlExpr::BinaryOp {
+                left: Box::new(SqlExpr::Ref(SqlFieldRef {
+                    object: "t0".to_string(),
+                    field: "name".to_string(),
+                })),
+                op,
+                right: Box::new(SqlExpr::Param(SqlParam::String(pattern.to_string()))),
+            })
+        };
+
+        let statement = SqliteDialect.query(&query(op(SqlOp::Like, "50\\%"), None, None));
+        assert!(
+            statement
+                .sql
+                .ends_with(r#"WHERE "t0"."name" LIKE ? ESCAPE '\'"#)
+        );
+
+        let statement = SqliteDialect.query(&query(op(SqlOp::Glob, "A*"), None, None));
+        assert!(statement.sql.ends_with(r#"WHERE "t0"."name" GLOB ?"#));
+    }
+
+    #[test]
+    fn test_bool_literal() {
+        let mut params = Vec::new();
+        assert_eq!(
+            SqliteDialect.expr(&SqlExpr::BoolLiteral(true), &mut params),
+            "1"
+        );
+        assert_eq!(
+            SqliteDialect.expr(&SqlExpr::BoolLiteral(false), &mut params),
+            "0"
+        );
+    }
+}
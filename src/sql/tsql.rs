@@ -0,0 +1,121 @@
+use crate::sql::SqlDialect;
+
+/// The Transact-SQL dialect used by Microsoft SQL Server.
+///
+/// Identifiers are quoted with square brackets, row limits are rendered using `TOP` (or
+/// `OFFSET ... FETCH` when rows are skipped), booleans are written as bits and string literals are
+/// written as Unicode (`N'...'`) literals.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct TSql;
+
+impl SqlDialect for TSql {
+    fn top(&self, limit: Option<u64>, offset: Option<u64>) -> Option<String> {
+        match offset {
+            None => limit.map(|n| format!("TOP ({})", n)),
+            Some(_) => None,
+        }
+    }
+
+    fn limit(&self, limit: Option<u64>, offset: Option<u64>) -> Option<String> {
+        // OFFSET requires an ORDER BY clause, ordering by a constant keeps the natural order.
+        let offset = offset?;
+        let mut sql = format!("ORDER BY (SELECT NULL) OFFSET {} ROWS", offset);
+        if let Some(limit) = limit {
+            sql.push_str(&format!(" FETCH NEXT {} ROWS ONLY", limit));
+        }
+
+        Some(sql)
+    }
+
+    fn bool_literal(&self, b: bool) -> String {
+        if b { "1" } else { "0" }.to_string()
+    }
+
+    fn identifier(&self, ident: &str) -> String {
+        format!("[{}]", ident.replace(']', "]]"))
+    }
+
+    fn string_literal(&self, s: &str) -> String {
+        format!("N'{}'", s.replace('\'', "''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{
+        SqlExpr, SqlFieldRef, SqlFromClause, SqlJoin, SqlJoinType, SqlObjectRef, SqlOp, SqlParam,
+        SqlQuery,
+    };
+
+    fn field(object: &str, field: &str) -> SqlFieldRef {
+        SqlFieldRef {
+            object: object.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_query() {
+        let query = SqlQuery {
+            projection: vec![field("o", "Order Id"), field("c", "Name")],
+            from: SqlFromClause {
+                object: SqlObjectRef {
+                    schema: None,
+                    object: "Order".to_string(),
+                    arguments: None,
+                    alias: "o".to_string(),
+                },
+                joins: vec![SqlJoin {
+                    join_type: SqlJoinType::Left,
+                    object: SqlObjectRef {
+                        schema: Some("sales".to_string()),
+                        object: "Customer".to_string(),
+                        arguments: None,
+                        alias: "c".to_string(),
+                    },
+                    on: SqlExpr::BinaryOp {
+                        left: Box::new(SqlExpr::Ref(field("o", "CustomerId"))),
+                        op: SqlOp::Eq,
+                        right: Box::new(SqlExpr::Ref(field("c", "Id"))),
+                    },
+                }],
+            },
+            where_clause: Some(SqlExpr::BinaryOp {
+                left: Box::new(SqlExpr::Ref(field("c", "Name"))),
+                op: SqlOp::Eq,
+                right: Box::new(SqlExpr::Param(SqlParam::String("x".to_string()))),
+            }),
+            limit: Some(5),
+            offset: None,
+        };
+
+        let statement = TSql.query(&query);
+        assert_eq!(
+            statement.sql,
+            "SELECT TOP (5) [o].[Order Id], [c].[Name] FROM [Order] AS [o] \
+             LEFT JOIN [sales].[Customer] AS [c] ON [o].[CustomerId] = [c].[Id] WHERE [c].[Name] = ?"
+        );
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(TSql.top(Some(5), Some(10)), None);
+        assert_eq!(
+            TSql.limit(Some(5), Some(10)).as_deref(),
+            Some("ORDER BY (SELECT NULL) OFFSET 10 ROWS FETCH NEXT 5 ROWS ONLY")
+        );
+    }
+
+    #[test]
+    fn test_identifier_escaping() {
+        assert_eq!(TSql.identifier("odd]name"), "[odd]]name]");
+    }
+
+    #[test]
+    fn test_string_literal() {
+        let mut params = Vec::new();
+        let sql = TSql.expr(&SqlExpr::StringLiteral("it's".to_string()), &mut params);
+        assert_eq!(sql, "N'it''s'");
+    }
+}
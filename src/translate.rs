@@ -0,0 +1,818 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+
+use crate::{
+    alg::{self, Name, Scored},
+    join::ForeignKeyGraph,
+    query::{Literal, ObjectTree, Operator, Predicate, Query},
+    schema::{ColumnId, DataType, Object, ObjectId, Parameter, ParameterMode, Schema},
+    sql::{
+        SqlExpr, SqlFieldRef, SqlFromClause, SqlJoin, SqlJoinType, SqlObjectRef, SqlOp, SqlParam,
+        SqlQuery,
+    },
+};
+
+/// Resolves the object and column names in a parsed query against a schema.
+///
+/// Names are matched using [`alg::find_best_mut`]. Column names in predicates are only matched
+/// against the columns of objects which appear in the query's object tree.
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub fn resolve<'a>(
+    query: Query<'a, String, String>,
+    schema: &mut Schema,
+) -> anyhow::Result<Query<'a, ObjectId, ColumnId>> {
+    let object = query
+        .object
+        .try_map(&mut |name| resolve_object(&name, schema))?;
+
+    // The parameters of a table-valued function are resolved alongside its columns, so that
+    // arguments can be given as predicates.
+    let candidates = object
+        .iter()
+        .filter_map(|id| schema.objects.get(*id))
+        .flat_map(|obj| {
+            let parameters = obj.parameters().iter().map(|p| p.column);
+            obj.columns().iter().copied().chain(parameters)
+        })
+        .collect::<HashSet<_>>();
+
+    let mut predicates = Vec::with_capacity(query.predicates.len());
+    for predicate in query.predicates {
+        let column = resolve_column(&predicate.identifier, &candidates, schema)?;
+        predicates.push(Predicate {
+            identifier: column,
+            operator: predicate.operator,
+            value: predicate.value,
+        });
+    }
+
+    Ok(Query { object, predicates })
+}
+
+/// Updates the scores of the objects and columns used by a resolved query to reflect their use.
+pub fn update_scores(query: &Query<ObjectId, ColumnId>, schema: &mut Schema) {
+    for id in query.object.iter() {
+        if let Some(obj) = schema.objects.get_mut(*id) {
+            alg::update_score(obj.score_mut());
+        }
+    }
+
+    for predicate in &query.predicates {
+        if let Some(column) = schema.columns.get_mut(predicate.identifier) {
+            alg::update_score(column.score_mut());
+        }
+    }
+}
+
+fn resolve_object(name: &str, schema: &mut Schema) -> anyhow::Result<ObjectId> {
+    let Some(obj) = alg::find_best_mut(name, schema.objects.values_mut()) else {
+        return Err(anyhow!("unknown object: {}", name));
+    };
+
+    tracing::debug!("Resolved object {} to {}", name, obj.qualified_name());
+    Ok(obj.id())
+}
+
+fn resolve_column(
+    name: &str,
+    candidates: &HashSet<ColumnId>,
+    schema: &mut Schema,
+) -> anyhow::Result<ColumnId> {
+    let columns = schema
+        .columns
+        .iter_mut()
+        .filter(|(id, _)| candidates.contains(id))
+        .map(|(_, column)| column);
+
+    let Some(column) = alg::find_best_mut(name, columns) else {
+        return Err(anyhow!("unknown column: {}", name));
+    };
+
+    tracing::debug!("Resolved column {} to {}", name, column.name);
+    Ok(column.id)
+}
+
+/// An object from a query's object tree along with the alias it is given in the generated SQL.
+struct AliasedObject {
+    id: ObjectId,
+    alias: String,
+
+    /// Index of this object's parent in the flattened object list.
+    parent: Option<usize>,
+}
+
+/// Flattens an object tree into a list of aliased objects in depth-first order.
+fn alias_objects(tree: &ObjectTree<ObjectId>) -> Vec<AliasedObject> {
+    fn visit(tree: &ObjectTree<ObjectId>, parent: Option<usize>, objects: &mut Vec<AliasedObject>) {
+        let index = objects.len();
+        objects.push(AliasedObject {
+            id: tree.root,
+            alias: format!("t{}", index),
+            parent,
+        });
+
+        for child in &tree.children {
+            visit(child, Some(index), objects);
+        }
+    }
+
+    let mut objects = Vec::new();
+    visit(tree, None, &mut objects);
+    objects
+}
+
+/// Gets the column pairs of the first foreign key from `obj` to the `referenced` object, as
+/// referencing and referenced columns. Composite foreign keys are stored as one [`ForeignKey`] per
+/// column, so every column of the same constraint is included.
+///
+/// [`ForeignKey`]: crate::schema::ForeignKey
+fn foreign_key_columns(obj: &Object, referenced: ObjectId) -> Option<Vec<(ColumnId, ColumnId)>> {
+    let foreign_keys = obj.foreign_keys();
+    let first = foreign_keys
+        .iter()
+        .find(|fk| fk.referenced_object == referenced)?;
+
+    let columns = match &first.constraint {
+        Some(constraint) => foreign_keys
+            .iter()
+            .filter(|fk| {
+                fk.referenced_object == referenced && fk.constraint.as_ref() == Some(constraint)
+            })
+            .map(|fk| (fk.column, fk.referenced_column))
+            .collect(),
+        None => vec![(first.column, first.referenced_column)],
+    };
+
+    Some(columns)
+}
+
+/// Builds the condition used to join a child object to its parent.
+///
+/// The condition is derived from a foreign key between the two objects, which may be declared on
+/// either of them. The columns of a composite foreign key are compared pairwise and combined using
+/// `AND`.
+fn join_condition(
+    schema: &Schema,
+    parent: &AliasedObject,
+    child: &AliasedObject,
+) -> anyhow::Result<SqlExpr> {
+    let parent_obj = schema
+        .objects
+        .get(parent.id)
+        .ok_or_else(|| anyhow!("unknown object: {:?}", parent.id))?;
+    let child_obj = schema
+        .objects
+        .get(child.id)
+        .ok_or_else(|| anyhow!("unknown object: {:?}", child.id))?;
+
+    // Pairs of parent and child columns which must be equal.
+    let outgoing = foreign_key_columns(parent_obj, child.id);
+    let incoming = || {
+        foreign_key_columns(child_obj, parent.id).map(|columns| {
+            columns
+                .into_iter()
+                .map(|(column, referenced_column)| (referenced_column, column))
+                .collect()
+        })
+    };
+
+    let columns = outgoing.or_else(incoming).unwrap_or_default();
+
+    let column_ref = |aliased: &AliasedObject, column_id: ColumnId| {
+        let column = schema
+            .columns
+            .get(column_id)
+            .ok_or_else(|| anyhow!("unknown column: {:?}", column_id))?;
+
+        anyhow::Ok(SqlExpr::Ref(SqlFieldRef {
+            object: aliased.alias.clone(),
+            field: column.name.clone(),
+        }))
+    };
+
+    let mut condition = None;
+    for (parent_col, child_col) in columns {
+        let eq = SqlExpr::BinaryOp {
+            left: Box::new(column_ref(parent, parent_col)?),
+            op: SqlOp::Eq,
+            right: Box::new(column_ref(child, child_col)?),
+        };
+
+        condition = Some(match condition {
+            None => eq,
+            Some(condition) => SqlExpr::BinaryOp {
+                left: Box::new(condition),
+                op: SqlOp::And,
+                right: Box::new(eq),
+            },
+        });
+    }
+
+    condition.ok_or_else(|| {
+        anyhow!(
+            "no foreign key between {} and {}",
+            parent_obj.name(),
+            child_obj.name()
+        )
+    })
+}
+
+/// Lowers a resolved query into a SQL query.
+///
+/// The projection consists of every column of every object in the query's object tree, in
+/// declaration order. Child objects are joined to their parents using a `LEFT JOIN` whose
+/// condition is derived from the foreign key between them. When two objects are not directly
+/// related, the shortest path through the foreign key graph is used and the intermediate objects
+/// are joined as well, without being projected. Predicates are combined using `AND`.
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub fn lower(query: &Query<'_, ObjectId, ColumnId>, schema: &Schema) -> anyhow::Result<SqlQuery> {
+    let objects = alias_objects(&query.object);
+
+    let mut projection = Vec::new();
+    for aliased in &objects {
+        let obj = schema
+            .objects
+            .get(aliased.id)
+            .ok_or_else(|| anyhow!("unknown object: {:?}", aliased.id))?;
+
+        for column_id in obj.columns() {
+            let column = schema
+                .columns
+                .get(*column_id)
+                .ok_or_else(|| anyhow!("unknown column: {:?}", column_id))?;
+
+            projection.push(SqlFieldRef {
+                object: aliased.alias.clone(),
+                field: column.name.clone(),
+            });
+        }
+    }
+
+    for aliased in &objects {
+        let obj = &schema.objects[aliased.id];
+        if !obj.is_queryable() {
+            return Err(anyhow!("cannot select from {}", obj.qualified_name()));
+        }
+
+        // Arguments can only be passed to a function at the root, and there is no foreign key to
+        // join a function on anyway.
+        if aliased.parent.is_some() && matches!(obj, Object::Function { .. }) {
+            return Err(anyhow!("cannot join to function {}", obj.qualified_name()));
+        }
+    }
+
+    // Predicates on the parameters of a table-valued function at the root are passed to it as
+    // arguments rather than used to filter its results.
+    let root = &objects[0];
+    let parameters = schema.objects[root.id].parameters();
+    let (arguments, predicates) = query.predicates.iter().partition::<Vec<_>, _>(|predicate| {
+        parameters.iter().any(|p| p.column == predicate.identifier)
+    });
+
+    let mut from = SqlFromClause {
+        object: object_ref(schema, root)?,
+        joins: Vec::new(),
+    };
+    if let Object::Function { .. } = &schema.objects[root.id] {
+        from.object.arguments = Some(function_arguments(schema, parameters, &arguments)?);
+    }
+
+    let graph = ForeignKeyGraph::new(schema);
+    let mut next_alias = objects.len();
+    for child in &objects[1..] {
+        let Some(parent) = child.parent.map(|index| &objects[index]) else {
+            continue;
+        };
+
+        let path = graph.find_path(parent.id, child.id)?;
+        let intermediates = path[1..path.len() - 1].iter().map(|id| {
+            let aliased = AliasedObject {
+                id: *id,
+                alias: format!("t{}", next_alias),
+                parent: None,
+            };
+            next_alias += 1;
+            aliased
+        });
+
+        let mut prev = None;
+        for aliased in intermediates.collect::<Vec<_>>().iter().chain([child]) {
+            let on = join_condition(schema, prev.unwrap_or(parent), aliased)?;
+            from.joins.push(SqlJoin {
+                join_type: SqlJoinType::Left,
+                object: object_ref(schema, aliased)?,
+                on,
+            });
+            prev = Some(aliased);
+        }
+    }
+
+    let mut where_clause = None;
+    for predicate in predicates {
+        let expr = predicate_expr(schema, &objects, predicate)?;
+        where_clause = Some(match where_clause {
+            None => expr,
+            Some(left) => SqlExpr::BinaryOp {
+                left: Box::new(left),
+                op: SqlOp::And,
+                right: Box::new(expr),
+            },
+        });
+    }
+
+    Ok(SqlQuery {
+        projection,
+        from,
+        where_clause,
+        limit: None,
+        offset: None,
+    })
+}
+
+/// Builds a reference to an object, qualified by its namespace if it has one.
+fn object_ref(schema: &Schema, aliased: &AliasedObject) -> anyhow::Result<SqlObjectRef> {
+    let obj = schema
+        .objects
+        .get(aliased.id)
+        .ok_or_else(|| anyhow!("unknown object: {:?}", aliased.id))?;
+
+    Ok(SqlObjectRef {
+        schema: obj.namespace().map(str::to_string),
+        object: obj.name().to_string(),
+        arguments: None,
+        alias: aliased.alias.clone(),
+    })
+}
+
+/// Builds the arguments passed to a function from the predicates given for its parameters.
+///
+/// Every input parameter must be given a value using `=`, and values are passed in the order the
+/// parameters are declared.
+fn function_arguments(
+    schema: &Schema,
+    parameters: &[Parameter],
+    predicates: &[&Predicate<'_, ColumnId>],
+) -> anyhow::Result<Vec<SqlExpr>> {
+    let mut arguments = Vec::new();
+    for parameter in parameters {
+        if parameter.mode == ParameterMode::Out {
+            continue;
+        }
+
+        let name = &schema.columns[parameter.column].name;
+        let Some(predicate) = predicates
+            .iter()
+            .find(|predicate| predicate.identifier == parameter.column)
+        else {
+            return Err(anyhow!("missing argument for parameter {}", name));
+        };
+
+        if !matches!(predicate.operator, Operator::Eq) {
+            return Err(anyhow!(
+                "argument for parameter {} must be given with =",
+                name
+            ));
+        }
+
+        arguments.push(SqlExpr::Param(literal_param(schema, predicate)));
+    }
+
+    Ok(arguments)
+}
+
+/// Builds a reference to a column, qualified by the alias of the first object which contains it.
+fn field_ref(
+    schema: &Schema,
+    objects: &[AliasedObject],
+    column_id: ColumnId,
+) -> anyhow::Result<SqlFieldRef> {
+    let column = schema
+        .columns
+        .get(column_id)
+        .ok_or_else(|| anyhow!("unknown column: {:?}", column_id))?;
+
+    let alias = objects
+        .iter()
+        .find(|aliased| {
+            schema
+                .objects
+                .get(aliased.id)
+                .is_some_and(|obj| obj.columns().contains(&column_id))
+        })
+        .map(|aliased| aliased.alias.clone())
+        .ok_or_else(|| {
+            anyhow!(
+                "column {} does not belong to any queried object",
+                column.name
+            )
+        })?;
+
+    Ok(SqlFieldRef {
+        object: alias,
+        field: column.name.clone(),
+    })
+}
+
+fn predicate_expr(
+    schema: &Schema,
+    objects: &[AliasedObject],
+    predicate: &Predicate<'_, ColumnId>,
+) -> anyhow::Result<SqlExpr> {
+    let op = match predicate.operator {
+        Operator::Eq => SqlOp::Eq,
+        Operator::Ne => SqlOp::Neq,
+        Operator::Lt => SqlOp::Lt,
+        Operator::Gt => SqlOp::Gt,
+        Operator::Le => SqlOp::Lte,
+        Operator::Ge => SqlOp::Gte,
+    };
+
+    Ok(SqlExpr::BinaryOp {
+        left: Box::new(SqlExpr::Ref(field_ref(
+            schema,
+            objects,
+            predicate.identifier,
+        )?)),
+        op,
+        right: Box::new(SqlExpr::Param(literal_param(schema, predicate))),
+    })
+}
+
+/// Converts the literal in a predicate into a parameter.
+///
+/// Literals are coerced to the type of the column they are compared with, so that a quoted number
+/// can be compared with an integer column and a bare number with a character column.
+fn literal_param(schema: &Schema, predicate: &Predicate<'_, ColumnId>) -> SqlParam {
+    let data_type = schema
+        .columns
+        .get(predicate.identifier)
+        .map(|column| &column.data_type);
+    match predicate.value {
+        Literal::String(s) if data_type.is_some_and(DataType::is_integer) => match s.parse() {
+            Ok(i) => SqlParam::Int(i),
+            Err(_) => SqlParam::String(s.to_string()),
+        },
+        Literal::String(s) => SqlParam::String(s.to_string()),
+        Literal::Integer(i) if data_type.is_some_and(DataType::is_text) => {
+            SqlParam::String(i.to_string())
+        }
+        Literal::Integer(i) => SqlParam::Int(i),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        query,
+        schema::builder::{ObjectBuilder, foreign_key},
+        sql::{Sql, SqlDialect, SqlStatement},
+    };
+
+    fn statement(input: &str, schema: &mut Schema) -> anyhow::Result<SqlStatement> {
+        let query = query::parse(input)?;
+        let query = resolve(query, schema)?;
+        let query = lower(&query, schema)?;
+        Ok(Sql.query(&query))
+    }
+
+    fn translate(input: &str, schema: &mut Schema) -> anyhow::Result<String> {
+        statement(input, schema).map(|statement| statement.sql)
+    }
+
+    #[test]
+    fn test_simple_query() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("users")
+            .columns(&["id", "name"])
+            .table(&mut schema);
+
+        let sql = translate("users", &mut schema).unwrap();
+        assert_eq!(sql, "SELECT t0.id, t0.name FROM users AS t0");
+    }
+
+    #[test]
+    fn test_namespaced_objects() {
+        let mut schema = Schema::default();
+        for namespace in ["dbo", "audit"] {
+            ObjectBuilder::new("users")
+                .namespace(namespace)
+                .columns(&["id"])
+                .table(&mut schema);
+        }
+
+        let sql = translate("audit.users", &mut schema).unwrap();
+        assert_eq!(sql, "SELECT t0.id FROM audit.users AS t0");
+    }
+
+    #[test]
+    fn test_table_valued_function() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("orders_for")
+            .parameters(&["@customer"], ParameterMode::In)
+            .columns(&["id", "total"])
+            .function(&mut schema);
+
+        let statement = statement("orders_for cust=7 total>100", &mut schema).unwrap();
+        assert_eq!(
+            statement.sql,
+            "SELECT t0.id, t0.total FROM orders_for(?) AS t0 WHERE t0.total > ?"
+        );
+        assert_eq!(statement.params, [SqlParam::Int(7), SqlParam::Int(100)]);
+
+        let err = translate("orders_for", &mut schema).unwrap_err();
+        assert_eq!(err.to_string(), "missing argument for parameter @customer");
+
+        ObjectBuilder::new("customers")
+            .columns(&["id"])
+            .table(&mut schema);
+        let err = translate("customers>orders_for cust=7", &mut schema).unwrap_err();
+        assert_eq!(err.to_string(), "cannot join to function orders_for");
+    }
+
+    #[test]
+    fn test_procedures_cannot_be_queried() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("archive")
+            .namespace("dbo")
+            .procedure(&mut schema);
+
+        let err = translate("archive", &mut schema).unwrap_err();
+        assert_eq!(err.to_string(), "cannot select from dbo.archive");
+    }
+
+    #[test]
+    fn test_fuzzy_names_with_predicates() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("user_accounts")
+            .columns(&["id", "display_name"])
+            .table(&mut schema);
+        ObjectBuilder::new("orders")
+            .columns(&["id", "total"])
+            .table(&mut schema);
+
+        let statement = statement("acc id=1 disp=bob", &mut schema).unwrap();
+        assert_eq!(
+            statement.sql,
+            "SELECT t0.id, t0.display_name FROM user_accounts AS t0 \
+             WHERE t0.id = ? AND t0.display_name = ?"
+        );
+        assert_eq!(
+            statement.params,
+            [SqlParam::Int(1), SqlParam::String("bob".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_update_scores() {
+        let mut schema = Schema::default();
+        let users = ObjectBuilder::new("users")
+            .columns(&["id"])
+            .table(&mut schema);
+
+        let query = resolve(query::parse("users id=1").unwrap(), &mut schema).unwrap();
+        assert!(schema.objects[users].score().is_none());
+
+        update_scores(&query, &mut schema);
+        assert!(schema.objects[users].score().is_some());
+        assert!(schema.columns.values().all(|c| c.score.is_some()));
+    }
+
+    #[test]
+    fn test_unknown_object() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("users")
+            .columns(&["id"])
+            .table(&mut schema);
+
+        let err = translate("orders", &mut schema).unwrap_err();
+        assert_eq!(err.to_string(), "unknown object: orders");
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("users")
+            .columns(&["id"])
+            .table(&mut schema);
+        ObjectBuilder::new("orders")
+            .columns(&["total"])
+            .table(&mut schema);
+
+        let err = translate("users total=1", &mut schema).unwrap_err();
+        assert_eq!(err.to_string(), "unknown column: total");
+    }
+
+    #[test]
+    fn test_join_on_child_foreign_key() {
+        let mut schema = Schema::default();
+        let users = ObjectBuilder::new("users")
+            .columns(&["id"])
+            .table(&mut schema);
+        let posts = ObjectBuilder::new("posts")
+            .columns(&["id", "user_id"])
+            .table(&mut schema);
+        foreign_key(&mut schema, posts, &["user_id"], users, &["id"]);
+
+        let sql = translate("users>posts", &mut schema).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT t0.id, t1.id, t1.user_id FROM users AS t0 \
+             LEFT JOIN posts AS t1 ON t0.id = t1.user_id"
+        );
+    }
+
+    #[test]
+    fn test_join_on_parent_foreign_key() {
+        let mut schema = Schema::default();
+        let users = ObjectBuilder::new("users")
+            .columns(&["id"])
+            .table(&mut schema);
+        let posts = ObjectBuilder::new("posts")
+            .columns(&["id", "user_id"])
+            .table(&mut schema);
+        foreign_key(&mut schema, posts, &["user_id"], users, &["id"]);
+
+        let sql = translate("posts>users", &mut schema).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT t0.id, t0.user_id, t1.id FROM posts AS t0 \
+             LEFT JOIN users AS t1 ON t0.user_id = t1.id"
+        );
+    }
+
+    #[test]
+    fn test_join_on_foreign_key() {
+        let mut schema = Schema::default();
+        let orders = ObjectBuilder::new("orders")
+            .columns(&["region", "number"])
+            .table(&mut schema);
+        let lines = ObjectBuilder::new("lines")
+            .columns(&["order_region", "order_number", "user_id"])
+            .table(&mut schema);
+        let users = ObjectBuilder::new("users")
+            .columns(&["id"])
+            .table(&mut schema);
+        foreign_key(&mut schema, lines, &["user_id"], users, &["id"]);
+        foreign_key(
+            &mut schema,
+            lines,
+            &["order_region", "order_number"],
+            orders,
+            &["region", "number"],
+        );
+
+        let sql = translate("orders>lines", &mut schema).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT t0.region, t0.number, t1.order_region, t1.order_number, t1.user_id \
+             FROM orders AS t0 LEFT JOIN lines AS t1 \
+             ON t0.region = t1.order_region AND t0.number = t1.order_number"
+        );
+
+        let sql = translate("lines>orders", &mut schema).unwrap();
+        assert!(sql.ends_with("ON t0.order_region = t1.region AND t0.order_number = t1.number"));
+    }
+
+    #[test]
+    fn test_join_nested_children() {
+        let mut schema = Schema::default();
+        let a = ObjectBuilder::new("a").columns(&["id"]).table(&mut schema);
+        let b = ObjectBuilder::new("b")
+            .columns(&["a_id"])
+            .table(&mut schema);
+        let c = ObjectBuilder::new("c")
+            .columns(&["id", "a_id"])
+            .table(&mut schema);
+        let d = ObjectBuilder::new("d")
+            .columns(&["c_id"])
+            .table(&mut schema);
+        foreign_key(&mut schema, b, &["a_id"], a, &["id"]);
+        foreign_key(&mut schema, c, &["a_id"], a, &["id"]);
+        foreign_key(&mut schema, d, &["c_id"], c, &["id"]);
+
+        let sql = translate("a>b+c>d", &mut schema).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT t0.id, t1.a_id, t2.id, t2.a_id, t3.c_id FROM a AS t0 \
+             LEFT JOIN b AS t1 ON t0.id = t1.a_id \
+             LEFT JOIN c AS t2 ON t0.id = t2.a_id \
+             LEFT JOIN d AS t3 ON t2.id = t3.c_id"
+        );
+    }
+
+    #[test]
+    fn test_join_without_foreign_key() {
+        let mut schema = Schema::default();
+        ObjectBuilder::new("users")
+            .columns(&["id"])
+            .table(&mut schema);
+        ObjectBuilder::new("orders")
+            .columns(&["id"])
+            .table(&mut schema);
+
+        let err = translate("users>orders", &mut schema).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no foreign key path between users and orders"
+        );
+    }
+
+    #[test]
+    fn test_join_through_intermediate_object() {
+        let mut schema = Schema::default();
+        let orders = ObjectBuilder::new("orders")
+            .columns(&["id"])
+            .table(&mut schema);
+        let lines = ObjectBuilder::new("order_lines")
+            .columns(&["order_id", "product_id"])
+            .table(&mut schema);
+        let products = ObjectBuilder::new("products")
+            .columns(&["id"])
+            .table(&mut schema);
+        foreign_key(&mut schema, lines, &["order_id"], orders, &["id"]);
+        foreign_key(&mut schema, lines, &["product_id"], products, &["id"]);
+
+        let sql = translate("orders>products", &mut schema).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT t0.id, t1.id FROM orders AS t0 \
+             LEFT JOIN order_lines AS t2 ON t0.id = t2.order_id \
+             LEFT JOIN products AS t1 ON t2.product_id = t1.id"
+        );
+    }
+
+    #[test]
+    fn test_join_path_prefers_scored_intermediate() {
+        let mut schema = Schema::default();
+        let a = ObjectBuilder::new("a").columns(&["id"]).table(&mut schema);
+        let x = ObjectBuilder::new("x")
+            .columns(&["a_id", "b_id"])
+            .table(&mut schema);
+        let y = ObjectBuilder::new("y")
+            .columns(&["a_id", "b_id"])
+            .table(&mut schema);
+        let b = ObjectBuilder::new("b").columns(&["id"]).table(&mut schema);
+        for link in [x, y] {
+            foreign_key(&mut schema, link, &["a_id"], a, &["id"]);
+            foreign_key(&mut schema, link, &["b_id"], b, &["id"]);
+        }
+
+        let err = translate("a>b", &mut schema).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ambiguous join path between a and b, candidates are: a>x>b, a>y>b"
+        );
+
+        alg::update_score(schema.objects[y].score_mut());
+        let sql = translate("a>b", &mut schema).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT t0.id, t1.id FROM a AS t0 \
+             LEFT JOIN y AS t2 ON t0.id = t2.a_id \
+             LEFT JOIN b AS t1 ON t2.b_id = t1.id"
+        );
+    }
+
+    #[test]
+    fn test_join_prefers_direct_foreign_key() {
+        let mut schema = Schema::default();
+        let a = ObjectBuilder::new("a").columns(&["id"]).table(&mut schema);
+        let b = ObjectBuilder::new("b")
+            .columns(&["id", "a_id"])
+            .table(&mut schema);
+        let x = ObjectBuilder::new("x")
+            .columns(&["a_id", "b_id"])
+            .table(&mut schema);
+        foreign_key(&mut schema, b, &["a_id"], a, &["id"]);
+        foreign_key(&mut schema, x, &["a_id"], a, &["id"]);
+        foreign_key(&mut schema, x, &["b_id"], b, &["id"]);
+
+        let sql = translate("a>b", &mut schema).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT t0.id, t1.id, t1.a_id FROM a AS t0 LEFT JOIN b AS t1 ON t0.id = t1.a_id"
+        );
+    }
+
+    #[test]
+    fn test_literals_are_coerced_to_column_type() {
+        let mut schema = Schema::default();
+        let users = ObjectBuilder::new("users")
+            .columns(&["id", "zip"])
+            .table(&mut schema);
+        let [id, zip] = schema.objects[users].columns() else {
+            panic!("expected two columns");
+        };
+        let (id, zip) = (*id, *zip);
+        schema.columns[id].data_type = DataType::Integer;
+        schema.columns[zip].data_type = DataType::VarChar(Some(10));
+
+        let statement = statement("users id='7' zip=1234", &mut schema).unwrap();
+        assert_eq!(
+            statement.params,
+            [SqlParam::Int(7), SqlParam::String("1234".to_string())]
+        );
+    }
+}